@@ -0,0 +1,245 @@
+/// JSON Canonicalization Scheme (RFC 8785)
+///
+/// {"b": 1.0E2, "a": [4.50, "é"]}  ->  {"a":[4.5,"é"],"b":100}
+///
+/// - no whitespaces
+/// - members sorted by the UTF-16 code units of their keys
+/// - numbers as IEEE 754 doubles, formatted as ECMAScript `Number.prototype.toString` does
+/// - strings with only `"`, `\` and control characters escaped
+use std::fmt;
+
+use crate::de::Value;
+use crate::ser::quote;
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum CanonicalError {
+    /// A number lexeme beyond the range of IEEE 754 doubles, such as "1e400".
+    NumberOutOfRange(String),
+}
+
+impl fmt::Display for CanonicalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CanonicalError::NumberOutOfRange(n) => {
+                write!(f, "number {} is out of the range of doubles", n)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CanonicalError {}
+
+/// Serializes into the canonical form, which is the same for all equivalent values.
+pub fn to_canonical_string(value: &Value) -> Result<String, CanonicalError> {
+    let mut out = String::new();
+    write(&mut out, value)?;
+    Ok(out)
+}
+
+/// The JCS form of a number lexeme, e.g. "100" for "1.0E2" and "1e+21" for "1e21".
+pub fn canonical_number(lexeme: &str) -> Result<String, CanonicalError> {
+    let out_of_range = || CanonicalError::NumberOutOfRange(lexeme.to_string());
+
+    let f: f64 = lexeme.parse().map_err(|_| out_of_range())?;
+    if !f.is_finite() {
+        return Err(out_of_range());
+    }
+    if f == 0.0 {
+        // Both 0 and -0.
+        return Ok("0".to_string());
+    }
+
+    // The shortest digits which round-trip, as "d.ddde-x".
+    let scientific = format!("{:e}", f.abs());
+    let (mantissa, exponent) = scientific.split_at(scientific.find('e').unwrap());
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let exponent: i32 = exponent[1..].parse().unwrap();
+
+    // ECMAScript Number::toString, where the value is 0.digits * 10^n.
+    let k = digits.len() as i32;
+    let n = exponent + 1;
+
+    let mut s = if f < 0.0 { "-" } else { "" }.to_string();
+    if k <= n && n <= 21 {
+        s.push_str(&digits);
+        s.push_str(&"0".repeat((n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        s.push_str(&digits[..n as usize]);
+        s.push('.');
+        s.push_str(&digits[n as usize..]);
+    } else if -6 < n && n <= 0 {
+        s.push_str("0.");
+        s.push_str(&"0".repeat(-n as usize));
+        s.push_str(&digits);
+    } else {
+        s.push_str(&digits[..1]);
+        if k > 1 {
+            s.push('.');
+            s.push_str(&digits[1..]);
+        }
+        s.push_str(&format!(
+            "e{}{}",
+            if n > 0 { "+" } else { "-" },
+            (n - 1).abs()
+        ));
+    }
+
+    Ok(s)
+}
+
+impl Value {
+    /// Rewrites every number lexeme into its JCS form.
+    pub fn canonicalize_numbers(&mut self) -> Result<(), CanonicalError> {
+        match self {
+            Value::Object(object) => object
+                .values_mut()
+                .try_for_each(|value| value.canonicalize_numbers()),
+            Value::Array(array) => array
+                .iter_mut()
+                .try_for_each(|value| value.canonicalize_numbers()),
+            Value::Number(n) => {
+                *n = canonical_number(n)?;
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn write(out: &mut String, value: &Value) -> Result<(), CanonicalError> {
+    match value {
+        Value::Object(object) => {
+            let mut members: Vec<_> = object.iter().collect();
+            members.sort_by(|a, b| a.0.encode_utf16().cmp(b.0.encode_utf16()));
+
+            out.push('{');
+            for (i, (key, value)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&quote(key));
+                out.push(':');
+                write(out, value)?;
+            }
+            out.push('}');
+        }
+        Value::Array(array) => {
+            out.push('[');
+            for (i, value) in array.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write(out, value)?;
+            }
+            out.push(']');
+        }
+        Value::String(s) => out.push_str(&quote(s)),
+        Value::Number(n) => out.push_str(&canonical_number(n)?),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Null => out.push_str("null"),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_number() {
+        // RFC 8785 appendix B, and ECMAScript formatting boundaries
+        let cases = [
+            ("0", "0"),
+            ("-0.0", "0"),
+            ("1.0E2", "100"),
+            ("4.50", "4.5"),
+            ("2e-3", "0.002"),
+            ("0.000001", "0.000001"),
+            ("1E-7", "1e-7"),
+            ("333333333.33333329", "333333333.3333333"),
+            ("1E30", "1e+30"),
+            ("1e21", "1e+21"),
+            ("999999999999999999999", "1e+21"),
+            ("295147905179352830000", "295147905179352830000"),
+            ("9007199254740993", "9007199254740992"),
+            ("-1.5e-9", "-1.5e-9"),
+            ("5e-324", "5e-324"),
+            ("1.7976931348623157e308", "1.7976931348623157e+308"),
+            ("0.000000000000000000000000001", "1e-27"),
+        ];
+
+        for (lexeme, expected) in cases.iter() {
+            assert_eq!(
+                canonical_number(lexeme).as_deref(),
+                Ok(*expected),
+                "{}",
+                lexeme
+            );
+        }
+
+        assert_eq!(
+            canonical_number("1e400"),
+            Err(CanonicalError::NumberOutOfRange("1e400".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_to_canonical_string() {
+        let number = |n: &str| Value::Number(n.to_string());
+
+        // RFC 8785 section 3.2.3
+        let keys = [
+            "\u{20ac}",
+            "\r",
+            "\u{fb33}",
+            "1",
+            "\u{1f600}",
+            "\u{80}",
+            "\u{f6}",
+        ];
+        let object = Value::Object(
+            keys.iter()
+                .map(|k| (k.to_string(), Value::String(k.to_string())))
+                .collect(),
+        );
+        let sorted: Vec<String> = [
+            "\r",
+            "1",
+            "\u{80}",
+            "\u{f6}",
+            "\u{20ac}",
+            "\u{1f600}",
+            "\u{fb33}",
+        ]
+        .iter()
+        .map(|k| format!("{0}:{0}", quote(k)))
+        .collect();
+        assert_eq!(
+            to_canonical_string(&object),
+            Ok(format!("{{{}}}", sorted.join(",")))
+        );
+
+        let mut value = crate::json!({
+            "numbers": [number("333333333.33333329"), number("1E30"), number("4.50"), number("2e-3")],
+            "string": "\u{20ac}$\u{f}\nA'B\"\\\\\"/",
+            "literals": [null, true, false]
+        });
+        assert_eq!(
+            to_canonical_string(&value),
+            Ok("{\"literals\":[null,true,false],\"numbers\":[333333333.3333333,1e+30,4.5,0.002],\"string\":\"\u{20ac}$\\u000f\\nA'B\\\"\\\\\\\\\\\"/\"}".to_string())
+        );
+
+        value.canonicalize_numbers().unwrap();
+        assert_eq!(
+            value.pointer("/numbers").unwrap(),
+            &crate::json!([
+                number("333333333.3333333"),
+                number("1e+30"),
+                number("4.5"),
+                number("0.002")
+            ])
+        );
+        assert!(to_canonical_string(&crate::json!([number("-1e400")])).is_err());
+    }
+}
@@ -0,0 +1,287 @@
+/// Comparison of values
+///
+/// Numbers are compared by their mathematical values, so "1", "1.0" and "1e0" are equal.
+///
+/// Values are totally ordered by type first, as null < bool < number < string < array < object.
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::de::Value;
+use crate::number::Decimal;
+
+/// A value which is compared and hashed semantically, e.g. as a `HashSet` element or a cache key.
+/// Numbers are equal if their mathematical values are, and members of objects are unordered.
+///
+/// `Value` itself compares and hashes number lexemes as they are.
+#[derive(Clone, Debug)]
+pub struct Semantic(pub Value);
+
+impl PartialEq for Semantic {
+    fn eq(&self, other: &Semantic) -> bool {
+        equals(&self.0, &other.0)
+    }
+}
+
+impl Eq for Semantic {}
+
+impl Hash for Semantic {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash(&self.0, true, state);
+    }
+}
+
+impl From<Value> for Semantic {
+    fn from(value: Value) -> Semantic {
+        Semantic(value)
+    }
+}
+
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash(self, false, state);
+    }
+}
+
+impl Value {
+    /// Equality which compares numbers by their mathematical values, as `Semantic` does.
+    pub fn semantic_eq(&self, other: &Value) -> bool {
+        equals(self, other)
+    }
+}
+
+/// Numbers are ordered by their mathematical values and then by their lexemes so as to be
+/// consistent with `==`, strings by their bytes, arrays lexicographically and objects by their
+/// members sorted by key. `-Infinity` comes before all numbers, `Infinity` after them, then `NaN`,
+/// and then any other lexeme which is not a number.
+impl Ord for Value {
+    fn cmp(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (Value::Object(l), Value::Object(r)) => {
+                let mut l: Vec<_> = l.iter().collect();
+                let mut r: Vec<_> = r.iter().collect();
+                l.sort();
+                r.sort();
+                l.cmp(&r)
+            }
+            (Value::Array(l), Value::Array(r)) => l.cmp(r),
+            (Value::String(l), Value::String(r)) => l.cmp(r),
+            (Value::Number(l), Value::Number(r)) => {
+                let key = |n: &str| {
+                    let decimal = Decimal::parse(n);
+                    let class = match n {
+                        _ if decimal.is_some() => 1,
+                        "-Infinity" => 0,
+                        "Infinity" => 2,
+                        "NaN" => 3,
+                        _ => 4,
+                    };
+                    (class, decimal)
+                };
+                key(l).cmp(&key(r)).then_with(|| l.cmp(r))
+            }
+            (Value::Bool(l), Value::Bool(r)) => l.cmp(r),
+            (l, r) => rank(l).cmp(&rank(r)),
+        }
+    }
+}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Value) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn rank(value: &Value) -> u8 {
+    match value {
+        Value::Null => 0,
+        Value::Bool(_) => 1,
+        Value::Number(_) => 2,
+        Value::String(_) => 3,
+        Value::Array(_) => 4,
+        Value::Object(_) => 5,
+    }
+}
+
+/// Hashes consistently with `equals` if `semantic`, or otherwise with `==`.
+fn hash<H: Hasher>(value: &Value, semantic: bool, state: &mut H) {
+    match value {
+        Value::Object(object) => {
+            state.write_u8(0);
+            state.write_usize(object.len());
+
+            // Sum the hashes of members so that their order does not matter.
+            let mut sum: u64 = 0;
+            for (key, value) in object.iter() {
+                let mut hasher = DefaultHasher::new();
+                key.hash(&mut hasher);
+                hash(value, semantic, &mut hasher);
+                sum = sum.wrapping_add(hasher.finish());
+            }
+            state.write_u64(sum);
+        }
+        Value::Array(array) => {
+            state.write_u8(1);
+            state.write_usize(array.len());
+            for value in array.iter() {
+                hash(value, semantic, state);
+            }
+        }
+        Value::String(s) => {
+            state.write_u8(2);
+            s.hash(state);
+        }
+        Value::Number(n) => match Decimal::parse(n).filter(|_| semantic) {
+            Some(decimal) => {
+                state.write_u8(3);
+                decimal.hash(state);
+            }
+            None => {
+                state.write_u8(4);
+                n.hash(state);
+            }
+        },
+        Value::Bool(b) => {
+            state.write_u8(5);
+            b.hash(state);
+        }
+        Value::Null => state.write_u8(6),
+    }
+}
+
+/// Equality as in JSONPath and JSON Patch `test`. Lexemes which are not numbers are compared as
+/// strings.
+pub(crate) fn equals(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(l), Value::Number(r)) => match (Decimal::parse(l), Decimal::parse(r)) {
+            (Some(l), Some(r)) => l == r,
+            _ => l == r,
+        },
+        (Value::Array(l), Value::Array(r)) => {
+            l.len() == r.len() && l.iter().zip(r.iter()).all(|(l, r)| equals(l, r))
+        }
+        (Value::Object(l), Value::Object(r)) => {
+            l.len() == r.len()
+                && l.iter()
+                    .all(|(k, l)| r.get(k).is_some_and(|r| equals(l, r)))
+        }
+        (l, r) => l == r,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeSet, HashSet};
+
+    use super::*;
+    use crate::de::parse;
+
+    #[test]
+    fn test_equals() {
+        assert!(equals(&parse("1"), &parse("1.0")));
+        assert!(equals(&parse("-0"), &parse("0.0e5")));
+        assert!(equals(
+            &parse(r#"{"a": [1, 2.50], "b": null}"#),
+            &parse(r#"{"b": null, "a": [1.0e0, 25.0e-1]}"#)
+        ));
+        assert!(!equals(&parse("[1, 2]"), &parse("[2, 1]")));
+        assert!(!equals(
+            &parse(r#"{"a": 1}"#),
+            &parse(r#"{"a": 1, "b": 1}"#)
+        ));
+        assert!(!equals(&parse("1"), &parse("\"1\"")));
+    }
+
+    fn hash_of<T: Hash>(value: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_semantic() {
+        let a = parse(r#"{"a": [1, 2.50], "b": {"c": null, "d": "x"}}"#);
+        let b = parse(r#"{"b": {"d": "x", "c": null}, "a": [1.0, 25.0e-1]}"#);
+
+        assert_ne!(a, b);
+        assert!(a.semantic_eq(&b));
+        assert_eq!(Semantic(a.clone()), Semantic(b.clone()));
+        assert_eq!(hash_of(&Semantic(a.clone())), hash_of(&Semantic(b)));
+        assert_ne!(Semantic(parse("[1]")), Semantic(parse("[\"1\"]")));
+
+        let set: HashSet<Semantic> = ["1", "1.0", "-0", "0.0", "\"1\"", "[1]", "[1.00]", "{}"]
+            .iter()
+            .map(|input| Semantic(parse(input)))
+            .collect();
+        assert_eq!(set.len(), 5);
+        assert!(set.contains(&Semantic(parse("10.0e-1"))));
+    }
+
+    #[test]
+    fn test_value_hash() {
+        let a = parse(r#"{"x": 1, "y": [true, null], "z": "s"}"#);
+        let b = parse(r#"{"z": "s", "y": [true, null], "x": 1}"#);
+        assert_eq!(a, b);
+        assert_eq!(hash_of(&a), hash_of(&b));
+
+        let set: HashSet<Value> = ["1", "1.0", "1", "[]", "{}", "null"]
+            .iter()
+            .map(|input| parse(input))
+            .collect();
+        assert_eq!(set.len(), 5);
+    }
+
+    #[test]
+    fn test_ord() {
+        let mut values: Vec<Value> = [
+            r#"{"a": 2}"#,
+            r#"{"a": 1, "b": 0}"#,
+            r#"{"a": 1}"#,
+            "[1, 2]",
+            "[1]",
+            "[]",
+            "\"b\"",
+            "\"a\"",
+            "10",
+            "9.5",
+            "1.0",
+            "1",
+            "-2",
+            "true",
+            "false",
+            "null",
+        ]
+        .iter()
+        .map(|input| parse(input))
+        .collect();
+        let sorted: Vec<Value> = values.iter().rev().cloned().collect();
+
+        values.sort();
+        assert_eq!(values, sorted);
+        assert_eq!(parse("1").cmp(&parse("1.0")), Ordering::Less);
+        assert!(parse("[]") < parse("{}"));
+        assert!(Value::Number("1e400".to_string()) < Value::Number("x".to_string()));
+
+        let mut numbers: Vec<Value> = ["NaN", "x", "1e400", "Infinity", "-1e400", "-Infinity", "0"]
+            .iter()
+            .map(|n| Value::Number(n.to_string()))
+            .collect();
+        numbers.sort();
+        assert_eq!(
+            numbers,
+            ["-Infinity", "-1e400", "0", "1e400", "Infinity", "NaN", "x"]
+                .iter()
+                .map(|n| Value::Number(n.to_string()))
+                .collect::<Vec<_>>()
+        );
+
+        let set: BTreeSet<Value> = ["3", "1", "2", "1", "null"]
+            .iter()
+            .map(|input| parse(input))
+            .collect();
+        assert_eq!(
+            set.into_iter().collect::<Vec<_>>(),
+            vec![parse("null"), parse("1"), parse("2"), parse("3")]
+        );
+    }
+}
@@ -0,0 +1,396 @@
+/// Conversions between Rust values and `Value`
+///
+/// - true                -> Value::Bool(true)
+/// - 42u8                -> Value::Number("42")
+/// - 0.1f64              -> Value::Number("0.1")
+/// - "a"                 -> Value::String("a")
+/// - vec![1, 2]          -> Value::Array([1, 2])
+/// - HashMap { "a": 1 }  -> Value::Object({ "a": 1 })
+/// - None::<u8>          -> Value::Null
+///
+/// Floats which are NaN or infinite panic, since JSON can not represent them. `Value::from_f64`
+/// returns `None` for them instead. `TryFrom<Value>` converts them all back, and fails for values
+/// of any other type or out of range.
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::BuildHasher;
+
+use crate::de::Value;
+use crate::number::{non_finite, non_finite_lexeme, Decimal};
+
+/// A `Value` which does not convert into the expected Rust type.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ConversionError {
+    /// Rust type name, such as "u8".
+    pub expected: &'static str,
+    /// Short description of the value, such as "string" or "number 300".
+    pub found: String,
+}
+
+impl ConversionError {
+    pub(crate) fn new(expected: &'static str, value: &Value) -> ConversionError {
+        let found = match value {
+            Value::Object(_) => "object".to_string(),
+            Value::Array(_) => "array".to_string(),
+            Value::String(_) => "string".to_string(),
+            Value::Number(n) => format!("number {}", n),
+            Value::Bool(b) => format!("boolean {}", b),
+            Value::Null => "null".to_string(),
+        };
+
+        ConversionError { expected, found }
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "expected {}, found {}", self.expected, self.found)
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+impl Value {
+    /// A number for a finite float, or `None` for NaN and infinity which JSON can not represent.
+    pub fn from_f64(f: f64) -> Option<Value> {
+        if f.is_finite() {
+            // `Debug` of floats is the shortest round-tripping representation, and is also a
+            // valid JSON number such as "1.0", "-0.5" or "1e-7".
+            Some(Value::Number(format!("{:?}", f)))
+        } else {
+            None
+        }
+    }
+
+    /// Like `from_f64`, without widening to `f64` which changes the shortest representation.
+    pub fn from_f32(f: f32) -> Option<Value> {
+        if f.is_finite() {
+            Some(Value::Number(format!("{:?}", f)))
+        } else {
+            None
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Value {
+        Value::Bool(b)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Value {
+        Value::String(s)
+    }
+}
+
+/// Panics at NaN and infinity. Use `Value::from_f64` for floats which may be either.
+impl From<f64> for Value {
+    fn from(f: f64) -> Value {
+        match Value::from_f64(f) {
+            Some(value) => value,
+            None => panic!(
+                "{} is not a JSON number; use `Value::from_f64`",
+                non_finite_lexeme(f)
+            ),
+        }
+    }
+}
+
+/// Panics at NaN and infinity. Use `Value::from_f32` for floats which may be either.
+impl From<f32> for Value {
+    fn from(f: f32) -> Value {
+        match Value::from_f32(f) {
+            Some(value) => value,
+            None => panic!(
+                "{} is not a JSON number; use `Value::from_f32`",
+                non_finite_lexeme(f64::from(f))
+            ),
+        }
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(vec: Vec<T>) -> Value {
+        Value::Array(vec.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Value>, S: BuildHasher> From<HashMap<String, T, S>> for Value {
+    fn from(map: HashMap<String, T, S>) -> Value {
+        Value::Object(map.into_iter().map(|(k, v)| (k, v.into())).collect())
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(option: Option<T>) -> Value {
+        option.map_or(Value::Null, Into::into)
+    }
+}
+
+impl TryFrom<Value> for bool {
+    type Error = ConversionError;
+
+    fn try_from(value: Value) -> Result<bool, ConversionError> {
+        match value {
+            Value::Bool(b) => Ok(b),
+            _ => Err(ConversionError::new("bool", &value)),
+        }
+    }
+}
+
+impl TryFrom<Value> for String {
+    type Error = ConversionError;
+
+    fn try_from(value: Value) -> Result<String, ConversionError> {
+        match value {
+            Value::String(s) => Ok(s),
+            _ => Err(ConversionError::new("String", &value)),
+        }
+    }
+}
+
+impl TryFrom<Value> for f64 {
+    type Error = ConversionError;
+
+    /// The nearest float. Numbers beyond the range of `f64` fail instead of becoming infinity,
+    /// though `NaN`, `Infinity` and `-Infinity` convert into themselves.
+    fn try_from(value: Value) -> Result<f64, ConversionError> {
+        match &value {
+            Value::Number(n) => match (non_finite(n), n.parse::<f64>()) {
+                (Some(f), _) => Ok(f),
+                (None, Ok(f)) if f.is_finite() => Ok(f),
+                _ => Err(ConversionError::new("f64", &value)),
+            },
+            _ => Err(ConversionError::new("f64", &value)),
+        }
+    }
+}
+
+impl TryFrom<Value> for f32 {
+    type Error = ConversionError;
+
+    /// The nearest float. Numbers beyond the range of `f32` fail instead of becoming infinity,
+    /// though `NaN`, `Infinity` and `-Infinity` convert into themselves.
+    fn try_from(value: Value) -> Result<f32, ConversionError> {
+        match &value {
+            Value::Number(n) => match (non_finite(n), n.parse::<f32>()) {
+                (Some(f), _) => Ok(f as f32),
+                (None, Ok(f)) if f.is_finite() => Ok(f),
+                _ => Err(ConversionError::new("f32", &value)),
+            },
+            _ => Err(ConversionError::new("f32", &value)),
+        }
+    }
+}
+
+impl<T: TryFrom<Value, Error = ConversionError>> TryFrom<Value> for Vec<T> {
+    type Error = ConversionError;
+
+    fn try_from(value: Value) -> Result<Vec<T>, ConversionError> {
+        match value {
+            Value::Array(array) => array.into_iter().map(T::try_from).collect(),
+            _ => Err(ConversionError::new("Vec", &value)),
+        }
+    }
+}
+
+impl<T: TryFrom<Value, Error = ConversionError>, S: BuildHasher + Default> TryFrom<Value>
+    for HashMap<String, T, S>
+{
+    type Error = ConversionError;
+
+    fn try_from(value: Value) -> Result<HashMap<String, T, S>, ConversionError> {
+        match value {
+            Value::Object(object) => object
+                .into_iter()
+                .map(|(k, v)| Ok((k, T::try_from(v)?)))
+                .collect(),
+            _ => Err(ConversionError::new("HashMap", &value)),
+        }
+    }
+}
+
+impl<T: TryFrom<Value, Error = ConversionError>> TryFrom<Value> for Option<T> {
+    type Error = ConversionError;
+
+    /// `null` is `None`, and anything else is converted into `T`.
+    fn try_from(value: Value) -> Result<Option<T>, ConversionError> {
+        match value {
+            Value::Null => Ok(None),
+            _ => T::try_from(value).map(Some),
+        }
+    }
+}
+
+macro_rules! integer {
+    ($($t:ident)*) => {
+        $(
+            impl From<$t> for Value {
+                fn from(n: $t) -> Value {
+                    Value::Number(n.to_string())
+                }
+            }
+
+            /// Any notation of an integer in range, such as "1", "1.0" or "1e2".
+            impl TryFrom<Value> for $t {
+                type Error = ConversionError;
+
+                fn try_from(value: Value) -> Result<$t, ConversionError> {
+                    let n = match &value {
+                        Value::Number(n) => Decimal::parse(n),
+                        _ => None,
+                    };
+
+                    // 40 digits are enough for any 128-bit integer.
+                    n.and_then(|n| n.to_integer_string(40))
+                        .and_then(|n| n.parse().ok())
+                        .ok_or_else(|| ConversionError::new(stringify!($t), &value))
+                }
+            }
+        )*
+    };
+}
+
+integer!(i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(lexeme: &str) -> Value {
+        Value::Number(lexeme.to_string())
+    }
+
+    #[test]
+    fn test_value_from() {
+        assert_eq!(Value::from(-7i8), number("-7"));
+        assert_eq!(Value::from(u64::MAX), number("18446744073709551615"));
+        assert_eq!(Value::from(0.1), number("0.1"));
+        assert_eq!(Value::from(1.0), number("1.0"));
+        assert_eq!(Value::from(1e300), number("1e300"));
+        assert_eq!(Value::from(-2.5e-8), number("-2.5e-8"));
+        assert_eq!(Value::from(0.1f32), number("0.1"));
+        assert_eq!(Value::from_f64(f64::INFINITY), None);
+        assert_eq!(Value::from_f32(f32::NEG_INFINITY), None);
+        assert_eq!(Value::from(Some("a")), Value::String("a".to_string()));
+        assert_eq!(Value::from(None::<bool>), Value::Null);
+        assert_eq!(
+            Value::from(vec![Some(1), None]),
+            Value::Array(vec![number("1"), Value::Null])
+        );
+
+        let mut map = HashMap::new();
+        map.insert("a".to_string(), vec![true]);
+        assert_eq!(Value::from(map), crate::json!({"a": [true]}));
+
+        // Every float converts into a valid lexeme which parses back to the same float.
+        for f in [
+            0.0,
+            -0.0,
+            5e-324,
+            1.7976931348623157e308,
+            123456.789,
+            1e21,
+            1e-7,
+        ]
+        .iter()
+        {
+            let value = Value::from(*f);
+            match &value {
+                Value::Number(n) => assert!(Decimal::parse(n).is_some(), "{}", n),
+                _ => panic!("Should be Number"),
+            }
+            assert_eq!(f64::try_from(value), Ok(*f));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "NaN is not a JSON number; use `Value::from_f64`")]
+    fn test_value_from_nan() {
+        let _ = Value::from(f64::NAN);
+    }
+
+    #[test]
+    #[should_panic(expected = "-Infinity is not a JSON number; use `Value::from_f32`")]
+    fn test_value_from_infinity() {
+        let _ = Value::from(f32::NEG_INFINITY);
+    }
+
+    #[test]
+    fn test_value_try_into() {
+        assert_eq!(u8::try_from(number("255")), Ok(255));
+        assert_eq!(i64::try_from(number("-1.20e2")), Ok(-120));
+        assert_eq!(u128::try_from(number("1e38")), Ok(10u128.pow(38)));
+        assert_eq!(f64::try_from(number("1")), Ok(1.0));
+        assert_eq!(f64::try_from(number("-Infinity")), Ok(f64::NEG_INFINITY));
+        assert!(f32::try_from(number("NaN")).unwrap().is_nan());
+        assert!(f64::try_from(number("inf")).is_err());
+        assert_eq!(bool::try_from(Value::Bool(true)), Ok(true));
+        assert_eq!(
+            String::try_from(Value::String("a".to_string())),
+            Ok("a".to_string())
+        );
+        assert_eq!(Option::<u8>::try_from(Value::Null), Ok(None));
+        assert_eq!(Option::<u8>::try_from(number("1")), Ok(Some(1)));
+        assert_eq!(
+            Vec::<Option<String>>::try_from(crate::json!(["a", null])),
+            Ok(vec![Some("a".to_string()), None])
+        );
+
+        let map: HashMap<String, Vec<u8>> =
+            HashMap::try_from(crate::json!({"a": [1, 2], "b": []})).unwrap();
+        assert_eq!(map["a"], vec![1, 2]);
+        assert_eq!(map["b"], vec![]);
+
+        let cases = [
+            (
+                u8::try_from(number("256")).unwrap_err(),
+                "expected u8, found number 256",
+            ),
+            (
+                u8::try_from(number("-1")).unwrap_err(),
+                "expected u8, found number -1",
+            ),
+            (
+                i32::try_from(number("1.5")).unwrap_err(),
+                "expected i32, found number 1.5",
+            ),
+            (
+                u64::try_from(number("1e400")).unwrap_err(),
+                "expected u64, found number 1e400",
+            ),
+            (
+                f64::try_from(number("1e400")).unwrap_err(),
+                "expected f64, found number 1e400",
+            ),
+            (
+                bool::try_from(Value::Null).unwrap_err(),
+                "expected bool, found null",
+            ),
+            (
+                String::try_from(Value::Bool(false)).unwrap_err(),
+                "expected String, found boolean false",
+            ),
+            (
+                Vec::<u8>::try_from(crate::json!([1, "2"])).unwrap_err(),
+                "expected u8, found string",
+            ),
+            (
+                HashMap::<String, u8>::try_from(crate::json!([])).unwrap_err(),
+                "expected HashMap, found array",
+            ),
+        ];
+
+        for (error, message) in cases.iter() {
+            assert_eq!(error.to_string(), *message);
+        }
+    }
+}
@@ -0,0 +1,743 @@
+/// Lossless concrete syntax tree
+///
+/// Every whitespace run, comment and the original spelling of keys and scalars are kept, so
+/// `Document::to_string()` reproduces the input byte-for-byte. Edits only re-emit the nodes they
+/// touch.
+///
+/// {
+///   "name": "jjsonsonpapaparser", // kept as is
+///   "desc": "toy json parser"
+/// }
+use std::fmt;
+
+use crate::de::{Deserializer, Value};
+use crate::pointer::parse_index;
+use crate::ser::quote;
+use crate::token;
+
+#[derive(Eq, PartialEq, Debug)]
+pub enum EditError {
+    NotFound,
+    NotContainer,
+    InvalidValue(token::Error),
+}
+
+/// A value with the trivia around it.
+#[derive(Clone, Eq, PartialEq, Debug)]
+struct Node {
+    leading: String,
+    kind: Kind,
+    trailing: String,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+enum Kind {
+    /// Raw lexeme of a string, number, boolean or null.
+    Scalar(String),
+    /// Elements, and the trivia inside of an empty array.
+    Array(Vec<Node>, String),
+    /// Members, and the trivia inside of an empty object.
+    Object(Vec<Member>, String),
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+struct Member {
+    leading: String,
+    /// Raw key including the quotation marks.
+    raw_key: String,
+    /// Unescaped key.
+    key: String,
+    before_colon: String,
+    value: Node,
+}
+
+#[derive(Eq, PartialEq, Debug)]
+pub struct Document {
+    root: Node,
+}
+
+impl Document {
+    pub fn parse(input: &str) -> Result<Document, token::Error> {
+        Parser::new(token::Tokenizer::new(input)).document()
+    }
+
+    /// Same as `parse`, but an error comes with the 1-based line and column of where parsing
+    /// stopped, and arrays and objects nested deeper than `max_depth` are `TooDeep` instead of
+    /// overflowing the stack.
+    pub fn parse_located(
+        input: &str,
+        max_depth: usize,
+    ) -> Result<Document, (token::Error, (usize, usize))> {
+        let mut parser = Parser::new(token::Tokenizer::new(input));
+        parser.max_depth = Some(max_depth);
+        parser.document().map_err(|e| {
+            let tokenizer = &parser.tokenizer;
+            (e, tokenizer.position_at(tokenizer.offset()))
+        })
+    }
+
+    /// Parses JSON with comments.
+    pub fn parse_jsonc(input: &str) -> Result<Document, token::Error> {
+        Parser::new(token::Tokenizer::with_comments(input)).document()
+    }
+
+    pub fn to_value(&self) -> Value {
+        self.root.kind.to_value()
+    }
+
+    /// Re-indents the document with `indent` per level. Whitespaces and comments are dropped,
+    /// but members keep their order and keys and scalars their spelling.
+    pub fn pretty(&self, indent: &str) -> String {
+        let mut out = String::new();
+        self.root.kind.format(&mut out, Some(indent), 0);
+        out
+    }
+
+    /// The document without any whitespaces or comments.
+    pub fn minify(&self) -> String {
+        let mut out = String::new();
+        self.root.kind.format(&mut out, None, 0);
+        out
+    }
+
+    /// Replaces the value at `path` with `json`.
+    pub fn set(&mut self, path: &[&str], json: &str) -> Result<(), EditError> {
+        let kind = fragment(json)?;
+        let node = self.root.lookup_mut(path).ok_or(EditError::NotFound)?;
+        node.kind = kind;
+        Ok(())
+    }
+
+    /// Inserts `json` at `path`. The last segment is a key to add (or overwrite) to an object, or
+    /// an index to insert before in an array. `-` appends to an array.
+    pub fn insert(&mut self, path: &[&str], json: &str) -> Result<(), EditError> {
+        let kind = fragment(json)?;
+        let (last, parent) = path.split_last().ok_or(EditError::NotFound)?;
+        let parent = self.root.lookup_mut(parent).ok_or(EditError::NotFound)?;
+
+        match &mut parent.kind {
+            Kind::Object(members, inner) => {
+                if let Some(member) = members.iter_mut().rev().find(|m| m.key == *last) {
+                    member.value.kind = kind;
+                    return Ok(());
+                }
+
+                let member = match members.last_mut() {
+                    Some(prev) => Member {
+                        leading: layout(&prev.leading),
+                        raw_key: quote(last),
+                        key: last.to_string(),
+                        before_colon: layout(&prev.before_colon),
+                        value: Node {
+                            leading: layout(&prev.value.leading),
+                            kind,
+                            trailing: std::mem::take(&mut prev.value.trailing),
+                        },
+                    },
+                    None => Member {
+                        leading: "".to_string(),
+                        raw_key: quote(last),
+                        key: last.to_string(),
+                        before_colon: "".to_string(),
+                        value: Node {
+                            leading: " ".to_string(),
+                            kind,
+                            trailing: std::mem::take(inner),
+                        },
+                    },
+                };
+                members.push(member);
+                Ok(())
+            }
+            Kind::Array(elements, inner) => {
+                let index = match *last {
+                    "-" => elements.len(),
+                    _ => parse_index(last)
+                        .filter(|i| *i <= elements.len())
+                        .ok_or(EditError::NotFound)?,
+                };
+
+                let element = if index < elements.len() {
+                    Node {
+                        leading: layout(&elements[index].leading),
+                        kind,
+                        trailing: "".to_string(),
+                    }
+                } else {
+                    match elements.last_mut() {
+                        Some(prev) => Node {
+                            leading: layout(&prev.leading),
+                            kind,
+                            trailing: std::mem::take(&mut prev.trailing),
+                        },
+                        None => Node {
+                            leading: "".to_string(),
+                            kind,
+                            trailing: std::mem::take(inner),
+                        },
+                    }
+                };
+                elements.insert(index, element);
+                Ok(())
+            }
+            Kind::Scalar(_) => Err(EditError::NotContainer),
+        }
+    }
+
+    /// Removes the object member or the array element at `path`.
+    pub fn remove(&mut self, path: &[&str]) -> Result<(), EditError> {
+        let (last, parent) = path.split_last().ok_or(EditError::NotFound)?;
+        let parent = self.root.lookup_mut(parent).ok_or(EditError::NotFound)?;
+
+        match &mut parent.kind {
+            Kind::Object(members, inner) => {
+                let index = members
+                    .iter()
+                    .rposition(|m| m.key == *last)
+                    .ok_or(EditError::NotFound)?;
+                let removed = members.remove(index);
+                let trailing = removed.value.trailing;
+
+                // Keep the trivia before the closing brace.
+                if index == members.len() {
+                    match members.last_mut() {
+                        Some(prev) => prev.value.trailing = trailing,
+                        None => *inner = removed.leading + &trailing,
+                    }
+                }
+                Ok(())
+            }
+            Kind::Array(elements, inner) => {
+                let index = parse_index(last)
+                    .filter(|i| *i < elements.len())
+                    .ok_or(EditError::NotFound)?;
+                let removed = elements.remove(index);
+
+                // Keep the trivia before the closing bracket.
+                if index == elements.len() {
+                    match elements.last_mut() {
+                        Some(prev) => prev.trailing = removed.trailing,
+                        None => *inner = removed.leading + &removed.trailing,
+                    }
+                }
+                Ok(())
+            }
+            Kind::Scalar(_) => Err(EditError::NotContainer),
+        }
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.root.fmt(f)
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.leading)?;
+        self.kind.fmt(f)?;
+        f.write_str(&self.trailing)
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Kind::Scalar(raw) => f.write_str(raw),
+            Kind::Array(elements, inner) => {
+                f.write_str("[")?;
+                if elements.is_empty() {
+                    f.write_str(inner)?;
+                }
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    element.fmt(f)?;
+                }
+                f.write_str("]")
+            }
+            Kind::Object(members, inner) => {
+                f.write_str("{")?;
+                if members.is_empty() {
+                    f.write_str(inner)?;
+                }
+                for (i, member) in members.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    f.write_str(&member.leading)?;
+                    f.write_str(&member.raw_key)?;
+                    f.write_str(&member.before_colon)?;
+                    f.write_str(":")?;
+                    member.value.fmt(f)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+impl Node {
+    fn lookup_mut(&mut self, path: &[&str]) -> Option<&mut Node> {
+        let (first, rest) = match path.split_first() {
+            Some(split) => split,
+            None => return Some(self),
+        };
+
+        let child = match &mut self.kind {
+            // The last of duplicate keys, which is the one `to_value` keeps.
+            Kind::Object(members, _) => members
+                .iter_mut()
+                .rev()
+                .find(|m| m.key == *first)
+                .map(|m| &mut m.value),
+            Kind::Array(elements, _) => parse_index(first).and_then(move |i| elements.get_mut(i)),
+            Kind::Scalar(_) => None,
+        };
+
+        child?.lookup_mut(rest)
+    }
+}
+
+impl Kind {
+    /// Writes the value without trivia, on multiple lines if `indent` is given.
+    fn format(&self, out: &mut String, indent: Option<&str>, depth: usize) {
+        let (open, close, len) = match self {
+            Kind::Scalar(raw) => {
+                out.push_str(raw);
+                return;
+            }
+            Kind::Array(elements, _) => ('[', ']', elements.len()),
+            Kind::Object(members, _) => ('{', '}', members.len()),
+        };
+
+        out.push(open);
+        for i in 0..len {
+            if i > 0 {
+                out.push(',');
+            }
+            if let Some(indent) = indent {
+                out.push('\n');
+                out.push_str(&indent.repeat(depth + 1));
+            }
+
+            match self {
+                Kind::Array(elements, _) => elements[i].kind.format(out, indent, depth + 1),
+                Kind::Object(members, _) => {
+                    out.push_str(&members[i].raw_key);
+                    out.push(':');
+                    if indent.is_some() {
+                        out.push(' ');
+                    }
+                    members[i].value.kind.format(out, indent, depth + 1);
+                }
+                Kind::Scalar(_) => unreachable!(),
+            }
+        }
+        if let (Some(indent), true) = (indent, len > 0) {
+            out.push('\n');
+            out.push_str(&indent.repeat(depth));
+        }
+        out.push(close);
+    }
+
+    fn to_value(&self) -> Value {
+        match self {
+            Kind::Scalar(raw) => match Deserializer::new(raw).parse() {
+                Ok(Some(value)) => value,
+                _ => unreachable!("scalars are validated while parsing"),
+            },
+            Kind::Array(elements, _) => {
+                Value::Array(elements.iter().map(|e| e.kind.to_value()).collect())
+            }
+            Kind::Object(members, _) => Value::Object(
+                members
+                    .iter()
+                    .map(|m| (m.key.clone(), m.value.kind.to_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// The line break and indentation of the trivia before a neighbor, without its comments, for a
+/// new member or element to be laid out in the same way.
+fn layout(trivia: &str) -> String {
+    let (line_break, indentation) = match trivia.rfind('\n') {
+        Some(i) if trivia[..i].ends_with('\r') => ("\r\n", &trivia[i + 1..]),
+        Some(i) => ("\n", &trivia[i + 1..]),
+        None => ("", trivia),
+    };
+    let indentation: String = indentation
+        .chars()
+        .take_while(|c| c.is_whitespace())
+        .collect();
+
+    format!("{}{}", line_break, indentation)
+}
+
+struct Parser<'a> {
+    tokenizer: token::Tokenizer<'a>,
+    max_depth: Option<usize>,
+    /// The number of arrays and objects around the current position.
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(tokenizer: token::Tokenizer<'a>) -> Parser<'a> {
+        Parser {
+            tokenizer,
+            max_depth: None,
+            depth: 0,
+        }
+    }
+
+    /// Error for a token which is not allowed here, or `Eof` for the end of the input.
+    fn unexpected(&mut self) -> token::Error {
+        match self.tokenizer.next() {
+            Err(e) => e,
+            Ok(_) => token::Error::InvalidToken,
+        }
+    }
+
+    fn document(&mut self) -> Result<Document, token::Error> {
+        let root = self.node()?;
+
+        // Nothing but trivia is allowed after the root value.
+        match self.tokenizer.next() {
+            Err(token::Error::Eof) => Ok(Document { root }),
+            Err(e) => Err(e),
+            Ok(_) => Err(token::Error::InvalidToken),
+        }
+    }
+
+    fn node(&mut self) -> Result<Node, token::Error> {
+        let leading = self.tokenizer.trivia()?.to_string();
+        let kind = self.kind()?;
+        let trailing = self.tokenizer.trivia()?.to_string();
+
+        Ok(Node {
+            leading,
+            kind,
+            trailing,
+        })
+    }
+
+    fn kind(&mut self) -> Result<Kind, token::Error> {
+        let start = self.tokenizer.offset();
+
+        match self.tokenizer.next()? {
+            Some(token::Token::LeftBrace) => self.nested(Parser::object),
+            Some(token::Token::LeftBracket) => self.nested(Parser::array),
+            Some(token::Token::String(_))
+            | Some(token::Token::Number(_))
+            | Some(token::Token::Bool(_))
+            | Some(token::Token::Null) => Ok(Kind::Scalar(
+                self.tokenizer
+                    .slice(start, self.tokenizer.offset())
+                    .to_string(),
+            )),
+            _ => Err(token::Error::InvalidToken),
+        }
+    }
+
+    /// Parses an array or object one level deeper.
+    fn nested(
+        &mut self,
+        parse: fn(&mut Parser<'a>) -> Result<Kind, token::Error>,
+    ) -> Result<Kind, token::Error> {
+        if self.max_depth == Some(self.depth) {
+            return Err(token::Error::TooDeep);
+        }
+
+        self.depth += 1;
+        let kind = parse(self);
+        self.depth -= 1;
+        kind
+    }
+
+    fn object(&mut self) -> Result<Kind, token::Error> {
+        let mut members = vec![];
+        let mut leading = self.tokenizer.trivia()?.to_string();
+
+        // empty object
+        if self.tokenizer.eat_token(token::Token::RightBrace) {
+            return Ok(Kind::Object(members, leading));
+        }
+
+        loop {
+            let start = self.tokenizer.offset();
+            let key = match self.tokenizer.next()? {
+                Some(token::Token::String(key)) => key,
+                _ => return Err(token::Error::InvalidToken),
+            };
+            let raw_key = self
+                .tokenizer
+                .slice(start, self.tokenizer.offset())
+                .to_string();
+            let before_colon = self.tokenizer.trivia()?.to_string();
+
+            // :
+            if !self.tokenizer.eat_token(token::Token::Colon) {
+                return Err(self.unexpected());
+            }
+
+            let value = self.node()?;
+
+            members.push(Member {
+                leading,
+                raw_key,
+                key,
+                before_colon,
+                value,
+            });
+
+            // }
+            if self.tokenizer.eat_token(token::Token::RightBrace) {
+                return Ok(Kind::Object(members, "".to_string()));
+            }
+
+            // ,
+            if !self.tokenizer.eat_token(token::Token::Comma) {
+                return Err(self.unexpected());
+            }
+
+            leading = self.tokenizer.trivia()?.to_string();
+        }
+    }
+
+    fn array(&mut self) -> Result<Kind, token::Error> {
+        let mut elements = vec![];
+        let inner = self.tokenizer.trivia()?.to_string();
+
+        // empty array
+        if self.tokenizer.eat_token(token::Token::RightBracket) {
+            return Ok(Kind::Array(elements, inner));
+        }
+
+        let mut leading = Some(inner);
+
+        loop {
+            let mut element = self.node()?;
+            if let Some(leading) = leading.take() {
+                element.leading = leading + &element.leading;
+            }
+            elements.push(element);
+
+            // ]
+            if self.tokenizer.eat_token(token::Token::RightBracket) {
+                return Ok(Kind::Array(elements, "".to_string()));
+            }
+
+            // ,
+            if !self.tokenizer.eat_token(token::Token::Comma) {
+                return Err(self.unexpected());
+            }
+        }
+    }
+}
+
+/// Parses a replacement value. The trivia around it is dropped.
+fn fragment(json: &str) -> Result<Kind, EditError> {
+    Document::parse(json)
+        .map(|document| document.root.kind)
+        .map_err(EditError::InvalidValue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_roundtrip() {
+        let inputs = [
+            "{}",
+            "  [ ]  ",
+            "\n{\n  \"name\" : \"jjsonson\\npapaparser\",\n  \"nums\": [1, -0.50, 1.0e+3 ]\t,\n  \"nested\": {\"a\" :{ } , \"b\": [ null,true,false]}\n}\n",
+            "\"\\/\"",
+        ];
+
+        for input in inputs.iter() {
+            let document = Document::parse(input).unwrap();
+            assert_eq!(document.to_string(), *input);
+        }
+
+        let input = "// header\n{\n  /* before key */ \"a\" /* before colon */: 1, // after a\n  \"b\": [ /* empty */ ]\n}\n// footer\n";
+        let document = Document::parse_jsonc(input).unwrap();
+        assert_eq!(document.to_string(), input);
+    }
+
+    #[test]
+    fn test_document_parse_error() {
+        assert_eq!(Document::parse("{"), Err(token::Error::Eof));
+        assert_eq!(Document::parse("{} {}"), Err(token::Error::InvalidToken));
+        assert_eq!(Document::parse("[1,]"), Err(token::Error::InvalidToken));
+        assert_eq!(
+            Document::parse("[1] // comment"),
+            Err(token::Error::InvalidToken)
+        );
+        assert_eq!(
+            Document::parse_jsonc("[1] /* comment"),
+            Err(token::Error::InvalidComment)
+        );
+        assert_eq!(
+            Document::parse_located("{\n  \"a\": 1,\n}", 8),
+            Err((token::Error::InvalidToken, (3, 2)))
+        );
+        assert!(Document::parse_located("[[{\"a\": []}]]", 4).is_ok());
+        assert_eq!(
+            Document::parse_located("[[{\"a\": []}]]", 3),
+            Err((token::Error::TooDeep, (1, 10)))
+        );
+    }
+
+    #[test]
+    fn test_document_to_value() {
+        let document = Document::parse(r#"{"a": [1, "x\ty"], "b": null}"#).unwrap();
+        match document.to_value() {
+            Value::Object(object) => {
+                assert_eq!(
+                    object["a"],
+                    Value::Array(vec![
+                        Value::Number("1".to_string()),
+                        Value::String("x\ty".to_string())
+                    ])
+                );
+                assert_eq!(object["b"], Value::Null);
+            }
+            _ => panic!("Should be Object"),
+        }
+    }
+
+    #[test]
+    fn test_document_pretty() {
+        let document = Document::parse_jsonc(
+            "{\"b\" :[1, 1.0E+3, {}], // comment\n\"a\":{\"\\n\": [ ]}, \"c\": {\"d\": null}}",
+        )
+        .unwrap();
+
+        assert_eq!(
+            document.pretty("  "),
+            "{\n  \"b\": [\n    1,\n    1.0E+3,\n    {}\n  ],\n  \"a\": {\n    \"\\n\": []\n  },\n  \"c\": {\n    \"d\": null\n  }\n}"
+        );
+        assert_eq!(
+            document.minify(),
+            "{\"b\":[1,1.0E+3,{}],\"a\":{\"\\n\":[]},\"c\":{\"d\":null}}"
+        );
+        assert_eq!(Document::parse(" \"a\" ").unwrap().pretty("\t"), "\"a\"");
+    }
+
+    #[test]
+    fn test_document_set() {
+        let input = "{\n  \"a\": 1.00, // one\n  \"b\": [ 1, 2 ]\n}\n";
+        let mut document = Document::parse_jsonc(input).unwrap();
+
+        document.set(&["b", "1"], " {\"c\" : 3} ").unwrap();
+        assert_eq!(
+            document.to_string(),
+            "{\n  \"a\": 1.00, // one\n  \"b\": [ 1, {\"c\" : 3} ]\n}\n"
+        );
+
+        assert_eq!(document.set(&["b", "2"], "0"), Err(EditError::NotFound));
+        assert_eq!(document.set(&["a", "0"], "0"), Err(EditError::NotFound));
+        assert_eq!(
+            document.set(&["a"], "[1"),
+            Err(EditError::InvalidValue(token::Error::Eof))
+        );
+    }
+
+    #[test]
+    fn test_document_insert() {
+        let mut document = Document::parse("{\n  \"a\": [\n    1\n  ]\n}\n").unwrap();
+
+        document.insert(&["b"], "true").unwrap();
+        assert_eq!(
+            document.to_string(),
+            "{\n  \"a\": [\n    1\n  ],\n  \"b\": true\n}\n"
+        );
+
+        document.insert(&["a", "-"], "3").unwrap();
+        document.insert(&["a", "1"], "2").unwrap();
+        document.insert(&["a", "0"], "0").unwrap();
+        assert_eq!(
+            document.to_string(),
+            "{\n  \"a\": [\n    0,\n    1,\n    2,\n    3\n  ],\n  \"b\": true\n}\n"
+        );
+
+        document.insert(&["b"], "false").unwrap();
+        document.insert(&["c\"d"], "{}").unwrap();
+        document.insert(&["c\"d", "e"], "[]").unwrap();
+        document.insert(&["c\"d", "e", "0"], "null").unwrap();
+        assert_eq!(
+            document.to_string(),
+            "{\n  \"a\": [\n    0,\n    1,\n    2,\n    3\n  ],\n  \"b\": false,\n  \"c\\\"d\": {\"e\": [null]}\n}\n"
+        );
+
+        assert_eq!(document.insert(&["a", "9"], "0"), Err(EditError::NotFound));
+        assert_eq!(
+            document.insert(&["b", "x"], "0"),
+            Err(EditError::NotContainer)
+        );
+        assert_eq!(document.insert(&[], "0"), Err(EditError::NotFound));
+
+        // Only the layout of the neighbor is copied, not its comments.
+        let mut document = Document::parse_jsonc("{\"a\": 1, // one\n \"b\": 2}").unwrap();
+        document.insert(&["c"], "3").unwrap();
+        assert_eq!(
+            document.to_string(),
+            "{\"a\": 1, // one\n \"b\": 2,\n \"c\": 3}"
+        );
+
+        let mut document = Document::parse_jsonc("[\r\n  // first\r\n  1\r\n]").unwrap();
+        document.insert(&["0"], "0").unwrap();
+        document.insert(&["-"], "2").unwrap();
+        assert_eq!(
+            document.to_string(),
+            "[\r\n  0,\r\n  // first\r\n  1,\r\n  2\r\n]"
+        );
+    }
+
+    #[test]
+    fn test_document_duplicate_keys() {
+        let mut document = Document::parse(r#"{"a": 1, "a": 2}"#).unwrap();
+
+        document.set(&["a"], "3").unwrap();
+        assert_eq!(document.to_string(), r#"{"a": 1, "a": 3}"#);
+        assert_eq!(document.to_value(), crate::json!({"a": 3}));
+
+        document.insert(&["a"], "4").unwrap();
+        assert_eq!(document.to_value(), crate::json!({"a": 4}));
+
+        document.remove(&["a"]).unwrap();
+        assert_eq!(document.to_string(), r#"{"a": 1}"#);
+    }
+
+    #[test]
+    fn test_document_remove() {
+        let mut document =
+            Document::parse("{\n  \"a\": [ 1, 2, 3 ],\n  \"b\": 2,\n  \"c\": 3\n}").unwrap();
+
+        document.remove(&["b"]).unwrap();
+        assert_eq!(
+            document.to_string(),
+            "{\n  \"a\": [ 1, 2, 3 ],\n  \"c\": 3\n}"
+        );
+
+        document.remove(&["c"]).unwrap();
+        assert_eq!(document.to_string(), "{\n  \"a\": [ 1, 2, 3 ]\n}");
+
+        document.remove(&["a", "2"]).unwrap();
+        document.remove(&["a", "0"]).unwrap();
+        assert_eq!(document.to_string(), "{\n  \"a\": [ 2 ]\n}");
+
+        document.remove(&["a", "0"]).unwrap();
+        assert_eq!(document.to_string(), "{\n  \"a\": [  ]\n}");
+
+        document.remove(&["a"]).unwrap();
+        assert_eq!(document.to_string(), "{\n  \n}");
+
+        assert_eq!(document.remove(&["a"]), Err(EditError::NotFound));
+    }
+}
@@ -0,0 +1,187 @@
+/// On-demand parsing
+///
+/// {"id": 7, "items": [{"name": "a"}, {"name": "b"}], "meta": {...}}
+///
+/// cursor.field("items")?;
+/// cursor.index(1)?;
+/// cursor.field("name")?;
+/// cursor.value()?;  // "b"
+///
+/// A cursor moves forward through the input, and builds only the values it is asked for. Values
+/// on the way are skipped without being built, but their syntax is still checked. The rest of
+/// the input after the last move is left unchecked.
+use crate::de::{Deserializer, Value};
+use crate::options::ParserOptions;
+use crate::raw::RawValue;
+use crate::token::{Error, Token};
+
+pub struct Cursor<'a> {
+    deserializer: Deserializer<'a>,
+}
+
+impl<'a> Cursor<'a> {
+    /// A cursor at the root value of `input`.
+    pub fn new(input: &'a str) -> Cursor<'a> {
+        Cursor::new_with_options(input, ParserOptions::default())
+    }
+
+    pub fn new_with_options(input: &'a str, options: ParserOptions) -> Cursor<'a> {
+        Cursor {
+            deserializer: Deserializer::new_with_options(input, options),
+        }
+    }
+
+    /// Moves past the value at the cursor without allocating anything.
+    pub fn skip_value(&mut self) -> Result<(), Error> {
+        self.deserializer.skip()
+    }
+
+    /// Builds the value at the cursor and moves past it.
+    pub fn value(&mut self) -> Result<Value, Error> {
+        self.deserializer.value()?.ok_or(Error::InvalidToken)
+    }
+
+    /// Captures the input text of the value at the cursor and moves past it.
+    pub fn raw_value(&mut self) -> Result<RawValue, Error> {
+        self.deserializer.raw()
+    }
+
+    /// Moves into the object at the cursor, to the value of the first member named `key`, since
+    /// the cursor can not look ahead for later ones whatever `ParserOptions::duplicate_keys` is.
+    /// Returns false having moved past the value if it is not an object or has no such member.
+    pub fn field(&mut self, key: &str) -> Result<bool, Error> {
+        let tokenizer = self.deserializer.tokenizer();
+
+        tokenizer.trivia()?;
+        if !tokenizer.eat_token(Token::LeftBrace) {
+            self.skip_value()?;
+            return Ok(false);
+        }
+        tokenizer.trivia()?;
+        if tokenizer.eat_token(Token::RightBrace) {
+            return Ok(false);
+        }
+
+        loop {
+            let tokenizer = self.deserializer.tokenizer();
+
+            tokenizer.trivia()?;
+            let found = match tokenizer.next()? {
+                Some(Token::String(name)) => name == key,
+                _ => return Err(Error::InvalidToken),
+            };
+            tokenizer.trivia()?;
+            if !tokenizer.eat_token(Token::Colon) {
+                return Err(Error::InvalidToken);
+            }
+
+            if found {
+                return Ok(true);
+            }
+            self.skip_value()?;
+
+            if self.deserializer.end_or_comma(Token::RightBrace)? {
+                return Ok(false);
+            }
+        }
+    }
+
+    /// Moves into the array at the cursor, to its element at `index`. Returns false having moved
+    /// past the value if it is not an array or is too short.
+    pub fn index(&mut self, index: usize) -> Result<bool, Error> {
+        let tokenizer = self.deserializer.tokenizer();
+
+        tokenizer.trivia()?;
+        if !tokenizer.eat_token(Token::LeftBracket) {
+            self.skip_value()?;
+            return Ok(false);
+        }
+        tokenizer.trivia()?;
+        if tokenizer.eat_token(Token::RightBracket) {
+            return Ok(false);
+        }
+
+        for _ in 0..index {
+            self.skip_value()?;
+
+            if self.deserializer.end_or_comma(Token::RightBracket)? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// 1-based line and column of the cursor, or of an error.
+    pub fn position(&self) -> (usize, usize) {
+        self.deserializer.position()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor() {
+        let input = r#"
+{
+  "id": 7,
+  "items": [{"name": "a", "tags": ["x", "y"]}, {"name": "b\n"}],
+  "meta": {"created": "2020-01-01"}
+}
+"#;
+
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.field("items"), Ok(true));
+        assert_eq!(cursor.index(1), Ok(true));
+        assert_eq!(cursor.field("name"), Ok(true));
+        assert_eq!(cursor.value(), Ok(Value::String("b\n".to_string())));
+
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.field("items"), Ok(true));
+        assert_eq!(cursor.index(0), Ok(true));
+        assert_eq!(cursor.field("tags"), Ok(true));
+        assert_eq!(cursor.raw_value().unwrap().get(), r#"["x", "y"]"#);
+
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.field("missing"), Ok(false));
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.field("items"), Ok(true));
+        assert_eq!(cursor.index(2), Ok(false));
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.field("id"), Ok(true));
+        assert_eq!(cursor.field("id"), Ok(false));
+        assert_eq!(cursor.index(0), Err(Error::InvalidToken));
+
+        let mut cursor = Cursor::new("[[1, {\"a\": [true]}], 2.50]");
+        assert_eq!(cursor.index(1), Ok(true));
+        assert_eq!(cursor.value(), Ok(Value::Number("2.50".to_string())));
+
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.skip_value(), Ok(()));
+        assert_eq!(cursor.position(), (6, 2));
+    }
+
+    #[test]
+    fn test_cursor_invalid() {
+        for input in [
+            r#"{"a": [1, 2,], "b": 1}"#,
+            r#"{"a": {"c" 1}, "b": 1}"#,
+            r#"{"a": "\x", "b": 1}"#,
+            r#"{"a": [01], "b": 1}"#,
+            r#"{"a": "#,
+        ]
+        .iter()
+        {
+            let mut cursor = Cursor::new(input);
+            assert!(cursor.field("b").is_err(), "{}", input);
+        }
+
+        let input = "{\"a\": [1, 2,], /* c */ \"b\": 1}";
+        let options = ParserOptions::new().comments(true).trailing_commas(true);
+        let mut cursor = Cursor::new_with_options(input, options);
+        assert_eq!(cursor.field("b"), Ok(true));
+        assert_eq!(cursor.value(), Ok(Value::Number("1".to_string())));
+    }
+}
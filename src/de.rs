@@ -1,8 +1,14 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::fmt;
+use std::str;
 
+use crate::jsonpath::{self, PathElement};
+use crate::options::{exceeds, DuplicateKeys, ParserOptions};
+use crate::raw::RawValue;
 use crate::token;
 
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Clone, Eq, PartialEq, Debug)]
 pub enum Value {
     Object(HashMap<String, Value>),
     Array(Vec<Value>),
@@ -14,86 +20,404 @@ pub enum Value {
 
 pub struct Deserializer<'a> {
     tokenizer: token::Tokenizer<'a>,
+    options: ParserOptions,
+    input_length: usize,
+    /// The number of values so far.
+    nodes: usize,
+    /// The number of arrays and objects around the current position.
+    depth: usize,
+    /// Offsets of the opening brackets of the arrays and objects being built.
+    open: Vec<usize>,
+    /// Keys and indices of the members and elements being built.
+    path: Vec<PathElement>,
+    /// Errors so far if recovering from them.
+    diagnostics: Option<Vec<Diagnostic>>,
+}
+
+/// An error found by `Deserializer::parse_recovering`.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Diagnostic {
+    pub error: token::Error,
+    /// 1-based, as `Deserializer::position`.
+    pub line: usize,
+    pub column: usize,
+    /// Byte offset in the input.
+    pub offset: usize,
+    /// As `Deserializer::path`.
+    pub path: String,
+}
+
+/// "LINE:COLUMN: message at `PATH`", without the path at the root.
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.error)?;
+        if self.path != "$" {
+            write!(f, " at `{}`", self.path)?;
+        }
+        Ok(())
+    }
 }
 
 impl Deserializer<'_> {
-    pub fn new(input: &str) -> Deserializer {
+    /// With `ParserOptions::default()`, which has no limit of nesting. Use `new_with_options` with
+    /// `ParserOptions::max_depth` for untrusted input.
+    pub fn new(input: &str) -> Deserializer<'_> {
+        Deserializer::new_with_options(input, ParserOptions::default())
+    }
+
+    pub fn new_with_options(input: &str, options: ParserOptions) -> Deserializer<'_> {
         Deserializer {
-            tokenizer: token::Tokenizer::new(input),
+            tokenizer: token::Tokenizer::with_options(input, &options),
+            options,
+            input_length: input.len(),
+            nodes: 0,
+            depth: 0,
+            open: vec![],
+            path: vec![],
+            diagnostics: None,
         }
     }
 
+    /// Parses a single value, which must be followed by nothing but whitespaces.
     pub fn parse(&mut self) -> Result<Option<Value>, token::Error> {
-        self.value()
+        self.check_input_length()?;
+        self.check_root()?;
+        let value = self.value()?;
+
+        self.trivia()?;
+        if !self.tokenizer.is_end() {
+            return Err(token::Error::InvalidToken);
+        }
+
+        Ok(value)
+    }
+
+    /// Same as `parse`, but goes on after errors to find all of them. Each broken element or
+    /// member is skipped up to the next `,`, `]` or `}`, and its value is replaced with `null`
+    /// if it has a key. Returns the value made of the rest, which is `None` if the root itself
+    /// is broken, with the errors in the order of their positions.
+    pub fn parse_recovering(&mut self) -> (Option<Value>, Vec<Diagnostic>) {
+        self.diagnostics = Some(vec![]);
+
+        let value = self
+            .check_input_length()
+            .and_then(|_| self.check_root())
+            .and_then(|_| self.value());
+        let value = match value {
+            Ok(value) => value,
+            Err(e) => {
+                self.fail(e).unwrap();
+                None
+            }
+        };
+
+        if value.is_some() {
+            let end = self.trivia().and_then(|_| match self.tokenizer.is_end() {
+                true => Ok(()),
+                false => Err(token::Error::InvalidToken),
+            });
+            if let Err(e) = end {
+                self.fail(e).unwrap();
+            }
+        }
+
+        (value, self.diagnostics.take().unwrap())
+    }
+
+    /// Same as `parse`, but only checks the syntax and captures the input text of the value
+    /// instead of building it.
+    pub fn parse_raw(&mut self) -> Result<Option<RawValue>, token::Error> {
+        self.check_input_length()?;
+        self.check_root()?;
+        let raw = self.raw()?;
+
+        self.trivia()?;
+        if !self.tokenizer.is_end() {
+            return Err(token::Error::InvalidToken);
+        }
+
+        Ok(Some(raw))
+    }
+
+    /// Members of the object at the top level, with their values captured as they are. `None` if
+    /// the value is not an object.
+    pub(crate) fn parse_raw_members(
+        &mut self,
+    ) -> Result<Option<Vec<(String, RawValue)>>, token::Error> {
+        self.trivia()?;
+        if !self.tokenizer.eat_token(token::Token::LeftBrace) {
+            return Ok(None);
+        }
+
+        let mut members = vec![];
+        self.trivia()?;
+        if !self.tokenizer.eat_token(token::Token::RightBrace) {
+            loop {
+                self.trivia()?;
+                let key = match self.tokenizer.next()? {
+                    Some(token::Token::String(key)) => key,
+                    _ => return Err(token::Error::InvalidToken),
+                };
+                self.trivia()?;
+                if !self.tokenizer.eat_token(token::Token::Colon) {
+                    return Err(token::Error::InvalidToken);
+                }
+                members.push((key, self.raw()?));
+
+                if self.end_or_comma(token::Token::RightBrace)? {
+                    break;
+                }
+            }
+        }
+
+        Ok(Some(members))
+    }
+
+    /// Elements of the array at the top level, captured as they are. `None` if the value is not
+    /// an array.
+    pub(crate) fn parse_raw_elements(&mut self) -> Result<Option<Vec<RawValue>>, token::Error> {
+        self.trivia()?;
+        if !self.tokenizer.eat_token(token::Token::LeftBracket) {
+            return Ok(None);
+        }
+
+        let mut elements = vec![];
+        self.trivia()?;
+        if !self.tokenizer.eat_token(token::Token::RightBracket) {
+            loop {
+                elements.push(self.raw()?);
+
+                if self.end_or_comma(token::Token::RightBracket)? {
+                    break;
+                }
+            }
+        }
+
+        Ok(Some(elements))
+    }
+
+    /// 1-based line and column of where parsing stopped, such as the location of an error.
+    /// Columns count characters.
+    pub fn position(&self) -> (usize, usize) {
+        self.tokenizer.position_at(self.tokenizer.offset())
+    }
+
+    /// The member or element being parsed, such as `$.servers[3].ports[1]`, which is where it
+    /// has failed after `parse` fails.
+    pub fn path(&self) -> String {
+        jsonpath::shorthand(&self.path)
     }
 
-    fn value(&mut self) -> Result<Option<Value>, token::Error> {
-        self.tokenizer.eat_whitespaces();
+    pub(crate) fn value(&mut self) -> Result<Option<Value>, token::Error> {
+        self.trivia()?;
+
+        self.count_node()?;
+
+        // Before eating the bracket, so that recovering skips the whole container.
+        if let Some('{') | Some('[') = self.tokenizer.peek_char() {
+            self.enter()?;
+            self.open.push(self.tokenizer.offset());
+        }
 
         match self.tokenizer.next()? {
-            Some(token::Token::LeftBrace) => self.object(),
-            Some(token::Token::LeftBracket) => self.array(),
-            Some(token::Token::String(string)) => Ok(Some(Value::String(string))),
-            Some(token::Token::Number(string)) => Ok(Some(Value::Number(string))),
+            Some(token::Token::LeftBrace) => {
+                let object = self.object();
+                self.depth -= 1;
+                self.open.pop();
+                object
+            }
+            Some(token::Token::LeftBracket) => {
+                let array = self.array();
+                self.depth -= 1;
+                self.open.pop();
+                array
+            }
+            Some(token::Token::String(string)) => {
+                self.check_string_length(string.len())?;
+                Ok(Some(Value::String(string)))
+            }
+            Some(token::Token::Number(string)) => {
+                self.check_number_length(string.len())?;
+                Ok(Some(Value::Number(string)))
+            }
             Some(token::Token::Bool(boolean)) => Ok(Some(Value::Bool(boolean))),
             Some(token::Token::Null) => Ok(Some(Value::Null)),
             _ => Err(token::Error::InvalidToken),
         }
     }
 
-    fn object(&mut self) -> Result<Option<Value>, token::Error> {
-        let mut object = HashMap::new();
+    pub(crate) fn raw(&mut self) -> Result<RawValue, token::Error> {
+        self.trivia()?;
 
-        // empty object
+        let start = self.tokenizer.offset();
+        self.skip()?;
+        let json = self.tokenizer.slice(start, self.tokenizer.offset());
+
+        Ok(RawValue::from_checked(json.to_string()))
+    }
+
+    /// Checks the syntax of a value and moves past it.
+    pub(crate) fn skip(&mut self) -> Result<(), token::Error> {
+        self.trivia()?;
+        self.count_node()?;
+
+        let start = self.tokenizer.offset();
+        match self.tokenizer.skip()? {
+            Some(token::Token::LeftBrace) => {
+                self.enter()?;
+                self.skip_members()?;
+                self.depth -= 1;
+                Ok(())
+            }
+            Some(token::Token::LeftBracket) => {
+                self.enter()?;
+                self.skip_elements()?;
+                self.depth -= 1;
+                Ok(())
+            }
+            // Without the quotation marks
+            Some(token::Token::String(_)) => {
+                self.check_string_length(self.tokenizer.offset() - start - 2)
+            }
+            Some(token::Token::Number(_)) => {
+                self.check_number_length(self.tokenizer.offset() - start)
+            }
+            Some(token::Token::Bool(_)) | Some(token::Token::Null) => Ok(()),
+            _ => Err(token::Error::InvalidToken),
+        }
+    }
+
+    /// Assumes that "{" has been already eaten.
+    fn skip_members(&mut self) -> Result<(), token::Error> {
+        self.trivia()?;
         if self.tokenizer.eat_token(token::Token::RightBrace) {
-            return Ok(Some(Value::Object(object)));
+            return Ok(());
         }
 
-        loop {
-            self.tokenizer.eat_whitespaces();
+        for members in 1.. {
+            self.check_members(members)?;
+
+            self.trivia()?;
+            let start = self.tokenizer.offset();
+            match self.tokenizer.skip()? {
+                Some(token::Token::String(_)) => {
+                    self.check_string_length(self.tokenizer.offset() - start - 2)?
+                }
+                _ => return Err(token::Error::InvalidToken),
+            }
+            self.trivia()?;
+            if !self.tokenizer.eat_token(token::Token::Colon) {
+                return Err(token::Error::InvalidToken);
+            }
+            self.skip()?;
 
-            match self.tokenizer.next()? {
-                Some(token::Token::String(key)) => {
-                    self.tokenizer.eat_whitespaces();
+            if self.end_or_comma(token::Token::RightBrace)? {
+                break;
+            }
+        }
 
-                    // :
-                    if !self.tokenizer.eat_token(token::Token::Colon) {
-                        break;
-                    }
+        Ok(())
+    }
 
-                    self.tokenizer.eat_whitespaces();
+    /// Assumes that "[" has been already eaten.
+    fn skip_elements(&mut self) -> Result<(), token::Error> {
+        self.trivia()?;
+        if self.tokenizer.eat_token(token::Token::RightBracket) {
+            return Ok(());
+        }
 
-                    // value
-                    match self.value()? {
-                        Some(value) => {
-                            object.insert(key, value);
-                        }
-                        _ => break,
-                    }
+        for elements in 1.. {
+            self.check_members(elements)?;
+            self.skip()?;
 
-                    self.tokenizer.eat_whitespaces();
+            if self.end_or_comma(token::Token::RightBracket)? {
+                break;
+            }
+        }
 
-                    // }
-                    if self.tokenizer.eat_token(token::Token::RightBrace) {
-                        return Ok(Some(Value::Object(object)));
-                    }
+        Ok(())
+    }
 
-                    // ,
-                    if !self.tokenizer.eat_token(token::Token::Comma) {
-                        break;
-                    }
+    /// Eats the closing bracket `end` and returns true, or eats a comma and returns false. A
+    /// trailing comma is eaten together with the closing bracket if allowed.
+    pub(crate) fn end_or_comma(&mut self, end: token::Token) -> Result<bool, token::Error> {
+        self.trivia()?;
+
+        if self.tokenizer.eat_token(end.clone()) {
+            Ok(true)
+        } else if self.tokenizer.eat_token(token::Token::Comma) {
+            self.trailing_comma(end)
+        } else {
+            match self.tokenizer.peek_char() {
+                // Likely the next element or member.
+                Some(c) if c.is_alphanumeric() || "\"'{[-".contains(c) => {
+                    Err(token::Error::MissingComma(closing(&end)))
                 }
-                _ => break,
+                _ => Err(self.unexpected()),
             }
         }
+    }
+
+    /// Eats the closing bracket `end` right after a comma if trailing commas are allowed, or
+    /// otherwise fails at it.
+    fn trailing_comma(&mut self, end: token::Token) -> Result<bool, token::Error> {
+        self.trivia()?;
+
+        if self.tokenizer.peek_char() != Some(closing(&end)) {
+            Ok(false)
+        } else if self.options.trailing_commas {
+            Ok(self.tokenizer.eat_token(end))
+        } else {
+            Err(token::Error::TrailingComma(closing(&end)))
+        }
+    }
 
-        Err(token::Error::InvalidToken)
+    /// Eats whitespaces, and also comments if they are allowed.
+    pub(crate) fn trivia(&mut self) -> Result<(), token::Error> {
+        self.tokenizer.trivia().map(|_| ())
+    }
+
+    fn object(&mut self) -> Result<Option<Value>, token::Error> {
+        let mut object = HashMap::new();
+        let mut members = 0;
+
+        self.trivia()?;
+
+        // empty object
+        if self.tokenizer.eat_token(token::Token::RightBrace) {
+            return Ok(Some(Value::Object(object)));
+        }
+
+        loop {
+            members += 1;
+            self.check_members(members)?;
+
+            // "key":
+            if let Some(key) = self.recover(|de| de.key())? {
+                // value, or a placeholder if it is broken
+                self.path.push(PathElement::Name(key));
+                let value = self.recover(|de| de.element())?.unwrap_or(Value::Null);
+                let key = match self.path.pop() {
+                    Some(PathElement::Name(key)) => key,
+                    _ => unreachable!(),
+                };
+
+                if let Err(e) = self.insert(&mut object, key, value) {
+                    self.fail(e)?;
+                }
+            }
+
+            // } or ,
+            if self.end_or_next(token::Token::RightBrace)? {
+                return Ok(Some(Value::Object(object)));
+            }
+        }
     }
 
     fn array(&mut self) -> Result<Option<Value>, token::Error> {
         let mut array = vec![];
 
-        self.tokenizer.eat_whitespaces();
+        self.trivia()?;
 
         // empty array
         if self.tokenizer.eat_token(token::Token::RightBracket) {
@@ -101,30 +425,255 @@ impl Deserializer<'_> {
         }
 
         loop {
-            self.tokenizer.eat_whitespaces();
+            self.check_members(array.len() + 1)?;
 
-            match self.value()? {
-                Some(value) => array.push(value),
-                _ => return Err(token::Error::InvalidToken),
+            // value, or a placeholder if it is broken
+            self.path.push(PathElement::Index(array.len()));
+            let value = self.recover(|de| de.element())?.unwrap_or(Value::Null);
+            self.path.pop();
+            array.push(value);
+
+            // ] or ,
+            if self.end_or_next(token::Token::RightBracket)? {
+                return Ok(Some(Value::Array(array)));
             }
+        }
+    }
 
-            self.tokenizer.eat_whitespaces();
+    /// A key and the following colon.
+    fn key(&mut self) -> Result<String, token::Error> {
+        self.trivia()?;
 
-            // ]
-            if self.tokenizer.eat_token(token::Token::RightBracket) {
-                return Ok(Some(Value::Array(array)));
+        // Not to eat what may close the object when recovering.
+        match self.tokenizer.peek_char() {
+            Some('"') => {}
+            Some('\'') => return Err(token::Error::SingleQuotedString),
+            _ => match self.tokenizer.peek_word() {
+                "" => return Err(self.unexpected()),
+                word => return Err(token::Error::UnquotedKey(word.to_string())),
+            },
+        }
+        let key = match self.tokenizer.next()? {
+            Some(token::Token::String(key)) => key,
+            _ => return Err(token::Error::InvalidToken),
+        };
+        self.check_string_length(key.len())?;
+
+        self.trivia()?;
+        if !self.tokenizer.eat_token(token::Token::Colon) {
+            return match self.unexpected() {
+                token::Error::InvalidToken => Err(token::Error::ExpectedColon(key)),
+                e => Err(e),
+            };
+        }
+
+        Ok(key)
+    }
+
+    /// A value in an array or object.
+    fn element(&mut self) -> Result<Value, token::Error> {
+        self.trivia()?;
+
+        // Not to eat what may close the container when recovering.
+        if let None | Some(',') | Some(']') | Some('}') = self.tokenizer.peek_char() {
+            return Err(self.unexpected());
+        }
+        self.value()?.ok_or(token::Error::InvalidToken)
+    }
+
+    fn insert(
+        &self,
+        object: &mut HashMap<String, Value>,
+        key: String,
+        value: Value,
+    ) -> Result<(), token::Error> {
+        match self.options.duplicate_keys {
+            DuplicateKeys::First => {
+                object.entry(key).or_insert(value);
+            }
+            DuplicateKeys::Last => {
+                object.insert(key, value);
+            }
+            DuplicateKeys::Error if object.contains_key(&key) => {
+                return Err(token::Error::DuplicateKey(key));
+            }
+            DuplicateKeys::Error => {
+                object.insert(key, value);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Same as `end_or_comma`, but when recovering from an error, goes on to the next element or
+    /// member, or gives up the container at a mismatched bracket or the end of the input.
+    fn end_or_next(&mut self, end: token::Token) -> Result<bool, token::Error> {
+        loop {
+            if let Some(done) = self.recover(|de| de.end_or_comma(end.clone()))? {
+                return Ok(done);
             }
 
-            // ,
-            if !self.tokenizer.eat_token(token::Token::Comma) {
-                break;
+            match self.tokenizer.peek_char() {
+                Some(',') => {}
+                Some(c) if c == closing(&end) => {}
+                _ => return Ok(true),
+            }
+        }
+    }
+
+    /// Runs `f`. When recovering, a failure is recorded and the input is skipped to the next
+    /// `,`, `]` or `}` on the same level, and then `None` is returned.
+    fn recover<T, F>(&mut self, f: F) -> Result<Option<T>, token::Error>
+    where
+        F: FnOnce(&mut Self) -> Result<T, token::Error>,
+    {
+        match f(self) {
+            Ok(t) => Ok(Some(t)),
+            Err(e) => {
+                self.fail(e)?;
+                self.tokenizer.resync();
+                Ok(None)
+            }
+        }
+    }
+
+    /// Records `error` when recovering, or otherwise returns it.
+    fn fail(&mut self, error: token::Error) -> Result<(), token::Error> {
+        let offset = self.tokenizer.offset();
+        let (line, column) = self.position();
+        let path = self.path();
+
+        match &mut self.diagnostics {
+            // One for each position, since an error often makes the enclosing containers fail
+            // there too.
+            Some(diagnostics) if diagnostics.last().map(|d| d.offset) == Some(offset) => Ok(()),
+            Some(diagnostics) => {
+                diagnostics.push(Diagnostic {
+                    error,
+                    line,
+                    column,
+                    offset,
+                    path,
+                });
+                Ok(())
+            }
+            None => Err(error),
+        }
+    }
+
+    /// An error at the end of the input, which is in the innermost open container if any, or
+    /// otherwise `Error::InvalidToken`.
+    fn unexpected(&self) -> token::Error {
+        if !self.tokenizer.is_end() {
+            return token::Error::InvalidToken;
+        }
+
+        match self.open.last() {
+            Some(&offset) => {
+                let (line, _) = self.tokenizer.position_at(offset);
+                match self.tokenizer.slice(offset, offset + 1) {
+                    "{" => token::Error::UnclosedObject(line),
+                    _ => token::Error::UnclosedArray(line),
+                }
             }
+            None => token::Error::Eof,
         }
+    }
+}
 
-        Err(token::Error::InvalidToken)
+/// The closing bracket of `end`.
+fn closing(end: &token::Token) -> char {
+    if *end == token::Token::RightBrace {
+        '}'
+    } else {
+        ']'
     }
 }
 
+impl Deserializer<'_> {
+    /// Checks that the root is an array or object unless scalars are allowed.
+    fn check_root(&mut self) -> Result<(), token::Error> {
+        if self.options.scalar_root {
+            return Ok(());
+        }
+
+        self.trivia()?;
+        match self.tokenizer.peek_char() {
+            Some('{') | Some('[') | None => Ok(()),
+            Some(_) => Err(token::Error::ScalarRoot),
+        }
+    }
+
+    fn check_input_length(&self) -> Result<(), token::Error> {
+        if exceeds(self.options.max_input_length, self.input_length) {
+            return Err(token::Error::InputTooLong);
+        }
+        Ok(())
+    }
+
+    fn check_string_length(&self, length: usize) -> Result<(), token::Error> {
+        if exceeds(self.options.max_string_length, length) {
+            return Err(token::Error::StringTooLong);
+        }
+        Ok(())
+    }
+
+    fn check_number_length(&self, length: usize) -> Result<(), token::Error> {
+        if exceeds(self.options.max_number_length, length) {
+            return Err(token::Error::NumberTooLong);
+        }
+        Ok(())
+    }
+
+    fn check_members(&self, members: usize) -> Result<(), token::Error> {
+        if exceeds(self.options.max_members, members) {
+            return Err(token::Error::TooManyMembers);
+        }
+        Ok(())
+    }
+
+    fn count_node(&mut self) -> Result<(), token::Error> {
+        self.nodes += 1;
+        if exceeds(self.options.max_nodes, self.nodes) {
+            return Err(token::Error::TooManyNodes);
+        }
+        Ok(())
+    }
+
+    /// Goes into an array or object.
+    fn enter(&mut self) -> Result<(), token::Error> {
+        if exceeds(self.options.max_depth, self.depth + 1) {
+            return Err(token::Error::TooDeep);
+        }
+        self.depth += 1;
+        Ok(())
+    }
+}
+
+impl<'a> Deserializer<'a> {
+    pub(crate) fn tokenizer(&mut self) -> &mut token::Tokenizer<'a> {
+        &mut self.tokenizer
+    }
+}
+
+/// Parses UTF-8 bytes as `Deserializer::parse` does. Invalid UTF-8 sequences are replaced with
+/// U+FFFD if `lossy_utf8`.
+pub fn from_slice(input: &[u8], options: ParserOptions) -> Result<Option<Value>, token::Error> {
+    let input = if options.lossy_utf8 {
+        String::from_utf8_lossy(input)
+    } else {
+        Cow::Borrowed(str::from_utf8(input).map_err(|_| token::Error::InvalidUtf8)?)
+    };
+
+    Deserializer::new_with_options(&input, options).parse()
+}
+
+/// Parses valid JSON in tests of the other modules.
+#[cfg(test)]
+pub(crate) fn parse(input: &str) -> Value {
+    Deserializer::new(input).parse().unwrap().unwrap()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -206,4 +755,395 @@ mod tests {
             _ => panic!("Should be Array"),
         }
     }
+
+    #[test]
+    fn test_deserializer_parse_nested() {
+        let input = r#"
+{
+  "name": {"nested": ["a", 1, {"deep": null}]},
+  "flags": [true, false]
+}
+"#;
+        let mut deserializer = Deserializer::new(input);
+        assert_eq!(
+            deserializer.parse().unwrap(),
+            Some(crate::json!({
+                "name": {"nested": ["a", 1, {"deep": null}]},
+                "flags": [true, false]
+            }))
+        );
+    }
+
+    #[test]
+    fn test_deserializer_trailing_input() {
+        assert_eq!(
+            Deserializer::new(" [1] \n").parse(),
+            Ok(Some(Value::Array(vec![Value::Number("1".to_string())])))
+        );
+
+        for input in ["1 2", "{} x", "[1]]", "null,"].iter() {
+            assert_eq!(
+                Deserializer::new(input).parse(),
+                Err(token::Error::InvalidToken),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_deserializer_position() {
+        let mut deserializer = Deserializer::new("{\n  \"a\": [1,\n    tru]\n}");
+        assert!(deserializer.parse().is_err());
+        assert_eq!(deserializer.position(), (3, 5));
+
+        let mut deserializer = Deserializer::new("\"日本\" x");
+        assert!(deserializer.parse().is_err());
+        assert_eq!(deserializer.position(), (1, 6));
+    }
+
+    #[test]
+    fn test_deserializer_limits() {
+        let parse = |input: &str, options: ParserOptions| {
+            Deserializer::new_with_options(input, options.clone()).parse()?;
+            Deserializer::new_with_options(input, options).parse_raw()
+        };
+        let input = r#"{"key": ["abc", 12.5, [true, null]]}"#;
+
+        let limits = ParserOptions::new()
+            .max_input_length(input.len())
+            .max_string_length(3)
+            .max_number_length(4)
+            .max_members(3)
+            .max_nodes(7)
+            .max_depth(3);
+        assert!(parse(input, limits.clone()).is_ok());
+
+        let cases = [
+            (
+                limits.clone().max_input_length(input.len() - 1),
+                token::Error::InputTooLong,
+            ),
+            (
+                limits.clone().max_string_length(2),
+                token::Error::StringTooLong,
+            ),
+            (
+                limits.clone().max_number_length(3),
+                token::Error::NumberTooLong,
+            ),
+            (limits.clone().max_members(2), token::Error::TooManyMembers),
+            (limits.clone().max_nodes(6), token::Error::TooManyNodes),
+            (limits.clone().max_depth(2), token::Error::TooDeep),
+        ];
+        for (options, error) in cases.iter() {
+            assert_eq!(
+                Deserializer::new_with_options(input, options.clone()).parse(),
+                Err(error.clone())
+            );
+            assert_eq!(
+                Deserializer::new_with_options(input, options.clone()).parse_raw(),
+                Err(error.clone())
+            );
+        }
+
+        let keys = ParserOptions::new().max_string_length(2);
+        assert_eq!(
+            parse(r#"{"abc": 1}"#, keys),
+            Err(token::Error::StringTooLong)
+        );
+        let scalars = ParserOptions::new().max_depth(0);
+        assert!(parse("1", scalars.clone()).is_ok());
+        assert_eq!(parse("[]", scalars), Err(token::Error::TooDeep));
+    }
+
+    #[test]
+    fn test_deserializer_options() {
+        let parse = |input: &str, options: ParserOptions| {
+            let value = Deserializer::new_with_options(input, options.clone()).parse();
+            let raw = Deserializer::new_with_options(input, options).parse_raw();
+            assert_eq!(raw.is_ok(), value.is_ok(), "{}", input);
+            value
+        };
+        let default = ParserOptions::new;
+        let number = |n: &str| Value::Number(n.to_string());
+
+        // strict
+        let input = "\"a\tb\\ud800\"";
+        assert_eq!(
+            parse(input, default()),
+            Ok(Some(Value::String("a\tb\u{fffd}".to_string())))
+        );
+        assert_eq!(
+            parse(input, default().strict(true)),
+            Err(token::Error::InvalidString)
+        );
+        assert_eq!(
+            parse("\"\\ud800\"", default().strict(true)),
+            Err(token::Error::InvalidEscapeChar)
+        );
+
+        // duplicate keys
+        let input = r#"{"a": 1, "b": 2, "a": 3}"#;
+        let a = |options| match Deserializer::new_with_options(input, options).parse() {
+            Ok(Some(Value::Object(object))) => Ok(object["a"].clone()),
+            Ok(_) => unreachable!(),
+            Err(e) => Err(e),
+        };
+        assert_eq!(a(default()), Ok(number("3")));
+        assert_eq!(
+            a(default().duplicate_keys(DuplicateKeys::Last)),
+            Ok(number("3"))
+        );
+        assert_eq!(
+            a(default().duplicate_keys(DuplicateKeys::First)),
+            Ok(number("1"))
+        );
+        assert_eq!(
+            a(default().duplicate_keys(DuplicateKeys::Error)),
+            Err(token::Error::DuplicateKey("a".to_string()))
+        );
+
+        // comments
+        let input = "// config\n{\"a\": /* one */ 1} // end";
+        assert_eq!(parse(input, default()), Err(token::Error::InvalidToken));
+        assert_eq!(
+            parse(input, default().comments(true)),
+            Ok(Some(crate::json!({"a": 1})))
+        );
+        assert_eq!(
+            parse("[1 /* x", default().comments(true)),
+            Err(token::Error::InvalidComment)
+        );
+
+        // trailing commas
+        for (input, end) in [("[1, [2,], ]", ']'), ("{\"a\": {\"b\": 1,},}", '}')].iter() {
+            assert_eq!(
+                parse(input, default()),
+                Err(token::Error::TrailingComma(*end))
+            );
+            assert!(parse(input, default().trailing_commas(true)).is_ok());
+        }
+        assert_eq!(
+            parse("[1,,]", default().trailing_commas(true)),
+            Err(token::Error::InvalidToken)
+        );
+        assert_eq!(
+            parse("[,]", default().trailing_commas(true)),
+            Err(token::Error::InvalidToken)
+        );
+
+        // NaN and Infinity
+        let input = "[NaN, Infinity, -Infinity, -1]";
+        assert_eq!(parse(input, default()), Err(token::Error::InvalidToken));
+        assert_eq!(
+            parse(input, default().non_finite_numbers(true)),
+            Ok(Some(Value::Array(vec![
+                number("NaN"),
+                number("Infinity"),
+                number("-Infinity"),
+                number("-1"),
+            ])))
+        );
+
+        // scalar root
+        assert_eq!(parse(" 1", default()), Ok(Some(number("1"))));
+        assert_eq!(
+            parse(" 1", default().scalar_root(false)),
+            Err(token::Error::ScalarRoot)
+        );
+        assert!(parse(" [1]", default().scalar_root(false)).is_ok());
+
+        // lossy UTF-8
+        let input = b"[\"a\xffb\"]";
+        assert_eq!(from_slice(input, default()), Err(token::Error::InvalidUtf8));
+        assert_eq!(
+            from_slice(input, default().lossy_utf8(true)),
+            Ok(Some(crate::json!(["a\u{fffd}b"])))
+        );
+        assert_eq!(from_slice(b"[1]", default()), Ok(Some(crate::json!([1]))));
+    }
+
+    #[test]
+    fn test_deserializer_grammar() {
+        let parse = |input: &str| Deserializer::new(input).parse();
+        let string = |s: &str| Ok(Some(Value::String(s.to_string())));
+
+        assert_eq!(parse("{ }"), Ok(Some(crate::json!({}))));
+        assert_eq!(
+            parse("[1e5, 2E-3, 0.5e+1]"),
+            Ok(Some(Value::Array(vec![
+                Value::Number("1e5".to_string()),
+                Value::Number("2E-3".to_string()),
+                Value::Number("0.5e+1".to_string()),
+            ])))
+        );
+        for input in ["-", "1.", "1e", "-a", "1.e5"].iter() {
+            assert_eq!(parse(input), Err(token::Error::InvalidNumber), "{}", input);
+        }
+
+        assert_eq!(parse(r#""\u0041\u00e9\u65E5""#), string("A\u{e9}\u{65e5}"));
+        assert_eq!(parse(r#""\ud83d\ude00""#), string("\u{1f600}"));
+        assert_eq!(parse(r#""\ude00\u0041""#), string("\u{fffd}A"));
+        assert_eq!(parse(r#""\u00g0""#), Err(token::Error::InvalidEscapeChar));
+    }
+
+    #[test]
+    fn test_deserializer_errors() {
+        use token::Error::*;
+
+        let parse = |input| Deserializer::new(input).parse().unwrap_err();
+        let cases = [
+            ("{\"name\" \"x\"}", ExpectedColon("name".to_string())),
+            ("{\"a\": 1 \"b\": 2}", MissingComma('}')),
+            ("[1 true]", MissingComma(']')),
+            ("[1, 2,]", TrailingComma(']')),
+            ("{name: 1}", UnquotedKey("name".to_string())),
+            ("{'a': 1}", SingleQuotedString),
+            ("[\"a\", 'b']", SingleQuotedString),
+            ("[\n\"a\", \"b]", UnterminatedString(2)),
+            ("{\n\"a\": [1,\n  {\"b\": 2", UnclosedObject(3)),
+            ("{\"a\": [1,\n", UnclosedArray(1)),
+            ("{\"a\"", UnclosedObject(1)),
+            ("[1 }", InvalidToken),
+            ("", Eof),
+        ];
+        for (input, error) in cases.iter() {
+            assert_eq!(&parse(input), error, "{}", input);
+        }
+
+        let strict = ParserOptions::new().strict(true);
+        assert_eq!(
+            Deserializer::new_with_options("[\"a\n\"]", strict).parse(),
+            Err(UnterminatedString(1))
+        );
+
+        assert_eq!(
+            parse("{\"name\" 1}").hint().as_deref(),
+            Some("write the member as \"name\": value")
+        );
+        assert_eq!(parse("{'a': 1}").to_string(), "single-quoted string");
+        assert_eq!(
+            parse("[[1]").to_string(),
+            "unexpected end of input in array opened at line 1"
+        );
+        assert_eq!(parse("[1 }").hint(), None);
+    }
+
+    #[test]
+    fn test_deserializer_path() {
+        let input = r#"{"servers": [{"ports": [1]}, {"a b": {}, "ports": [80, x]}]}"#;
+        let mut deserializer = Deserializer::new(input);
+        assert_eq!(deserializer.path(), "$");
+        assert!(deserializer.parse().is_err());
+        assert_eq!(deserializer.path(), "$.servers[1].ports[1]");
+
+        let (_, diagnostics) =
+            Deserializer::new(r#"[{"a": [1, {"\n": nul}]}, 'b', [}]"#).parse_recovering();
+        let paths: Vec<_> = diagnostics.iter().map(|d| d.path.as_str()).collect();
+        assert_eq!(paths, vec!["$[0].a[1]['\\n']", "$[1]", "$[2][0]"]);
+        assert_eq!(
+            diagnostics[1].to_string(),
+            "1:27: single-quoted string at `$[1]`"
+        );
+    }
+
+    #[test]
+    fn test_deserializer_recovering() {
+        let recover = |input| {
+            let (value, diagnostics) = Deserializer::new(input).parse_recovering();
+            let errors: Vec<_> = diagnostics
+                .iter()
+                .map(|d| (d.line, d.column, d.error.clone()))
+                .collect();
+            (value, errors)
+        };
+
+        let input = r#"{
+  "a": nul,
+  "b" 1,
+  "c": [1 2, {"d": }, 3],
+  "e": "ok",
+  1: 2
+}"#;
+        assert_eq!(
+            recover(input),
+            (
+                Some(crate::json!({
+                    "a": null,
+                    "c": [Value::Number("1".to_string()), {"d": null}, Value::Number("3".to_string())],
+                    "e": "ok"
+                })),
+                vec![
+                    (2, 8, token::Error::InvalidToken),
+                    (3, 7, token::Error::ExpectedColon("b".to_string())),
+                    (4, 11, token::Error::MissingComma(']')),
+                    (4, 20, token::Error::InvalidToken),
+                    (6, 3, token::Error::UnquotedKey("1".to_string())),
+                ]
+            )
+        );
+
+        // A valid input is the same as `parse`.
+        let input = r#"{"a": [1, {"b": null}]}"#;
+        assert_eq!(
+            recover(input),
+            (Deserializer::new(input).parse().unwrap(), vec![])
+        );
+
+        // An unterminated container is closed at the end.
+        assert_eq!(
+            recover("[1, [2"),
+            (
+                Some(crate::json!([
+                    Value::Number("1".to_string()),
+                    [Value::Number("2".to_string())]
+                ])),
+                vec![(1, 7, token::Error::UnclosedArray(1))]
+            )
+        );
+        assert_eq!(
+            recover("x"),
+            (None, vec![(1, 1, token::Error::InvalidToken)])
+        );
+        assert_eq!(
+            recover("[] 1"),
+            (
+                Some(crate::json!([])),
+                vec![(1, 4, token::Error::InvalidToken)]
+            )
+        );
+        assert_eq!(
+            recover(r#"{"a": 1, "a": 2}"#).1.len(),
+            0,
+            "duplicate keys are fine by default"
+        );
+        // The depth is back after a container too deep, so its siblings are fine.
+        let (value, diagnostics) =
+            Deserializer::new_with_options("[[[1]], [2], [3]]", ParserOptions::new().max_depth(2))
+                .parse_recovering();
+        assert_eq!(
+            value,
+            Some(crate::json!([
+                [null],
+                [Value::Number("2".to_string())],
+                [Value::Number("3".to_string())]
+            ]))
+        );
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|d| (d.line, d.column, d.error.clone()))
+                .collect::<Vec<_>>(),
+            vec![(1, 3, token::Error::TooDeep)]
+        );
+
+        let (_, diagnostics) = Deserializer::new_with_options(
+            r#"{"a": 1, "a": 2, "b": [}"#,
+            ParserOptions::new().duplicate_keys(DuplicateKeys::Error),
+        )
+        .parse_recovering();
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].to_string(), "1:16: duplicate key \"a\"");
+    }
 }
@@ -0,0 +1,209 @@
+/// Structural diff of two values
+///
+/// - /port: 80
+/// + /port: 8080
+/// + /debug: true
+/// - /legacy: null
+///
+/// Members are compared by key regardless of their order, and array elements by index. Numbers
+/// are compared by their mathematical values unless `DiffOptions::exact_numbers` is set.
+use std::fmt;
+
+use crate::cmp;
+use crate::de::Value;
+use crate::patch::Operation;
+use crate::pointer;
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Change {
+    Added {
+        path: String,
+        value: Value,
+    },
+    Removed {
+        path: String,
+        value: Value,
+    },
+    Changed {
+        path: String,
+        from: Value,
+        to: Value,
+    },
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct DiffOptions {
+    /// Whether numbers of different spellings such as `1.0` and `1.00` differ.
+    pub exact_numbers: bool,
+}
+
+impl Change {
+    /// JSON Pointer to the changed value.
+    pub fn path(&self) -> &str {
+        match self {
+            Change::Added { path, .. }
+            | Change::Removed { path, .. }
+            | Change::Changed { path, .. } => path,
+        }
+    }
+
+    pub fn to_operation(&self) -> Operation {
+        match self {
+            Change::Added { path, value } => Operation::Add {
+                path: path.clone(),
+                value: value.clone(),
+            },
+            Change::Removed { path, .. } => Operation::Remove { path: path.clone() },
+            Change::Changed { path, to, .. } => Operation::Replace {
+                path: path.clone(),
+                value: to.clone(),
+            },
+        }
+    }
+}
+
+/// Lines of a unified view, "-" for the old and "+" for the new value.
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Change::Added { path, value } => write!(f, "+ {}: {}", path, value),
+            Change::Removed { path, value } => write!(f, "- {}: {}", path, value),
+            Change::Changed { path, from, to } => {
+                write!(f, "- {}: {}\n+ {}: {}", path, from, path, to)
+            }
+        }
+    }
+}
+
+/// Changes which turn `from` into `to`, with the default options.
+pub fn diff(from: &Value, to: &Value) -> Vec<Change> {
+    diff_with(from, to, &DiffOptions::default())
+}
+
+/// Changes which turn `from` into `to`, ordered by path except that removed array elements are
+/// listed from the last one. So applying them in order as a JSON Patch (`Change::to_operation`)
+/// also turns `from` into `to`.
+pub fn diff_with(from: &Value, to: &Value, options: &DiffOptions) -> Vec<Change> {
+    let mut changes = vec![];
+    walk(from, to, "", options, &mut changes);
+    changes
+}
+
+fn walk(from: &Value, to: &Value, path: &str, options: &DiffOptions, changes: &mut Vec<Change>) {
+    match (from, to) {
+        (Value::Object(from), Value::Object(to)) => {
+            let mut keys: Vec<&String> = from.keys().chain(to.keys()).collect();
+            keys.sort();
+            keys.dedup();
+
+            for key in keys {
+                let path = format!("{}/{}", path, pointer::escape(key));
+                match (from.get(key), to.get(key)) {
+                    (Some(a), Some(b)) => walk(a, b, &path, options, changes),
+                    (Some(a), None) => changes.push(Change::Removed {
+                        path,
+                        value: a.clone(),
+                    }),
+                    (None, Some(b)) => changes.push(Change::Added {
+                        path,
+                        value: b.clone(),
+                    }),
+                    (None, None) => unreachable!(),
+                }
+            }
+        }
+        (Value::Array(from), Value::Array(to)) => {
+            for (i, (a, b)) in from.iter().zip(to.iter()).enumerate() {
+                walk(a, b, &format!("{}/{}", path, i), options, changes);
+            }
+            for i in (to.len()..from.len()).rev() {
+                changes.push(Change::Removed {
+                    path: format!("{}/{}", path, i),
+                    value: from[i].clone(),
+                });
+            }
+            for (i, value) in to.iter().enumerate().skip(from.len()) {
+                changes.push(Change::Added {
+                    path: format!("{}/{}", path, i),
+                    value: value.clone(),
+                });
+            }
+        }
+        (Value::Number(a), Value::Number(b)) if options.exact_numbers && a != b => {
+            changes.push(Change::Changed {
+                path: path.to_string(),
+                from: from.clone(),
+                to: to.clone(),
+            })
+        }
+        _ if cmp::equals(from, to) => {}
+        _ => changes.push(Change::Changed {
+            path: path.to_string(),
+            from: from.clone(),
+            to: to.clone(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::de::parse;
+    use crate::patch::apply_patch;
+
+    #[test]
+    fn test_diff() {
+        let from = parse(r#"{"port": 80, "ratio": 1.0, "hosts": ["a", "b", "c"], "legacy": null}"#);
+        let to = parse(r#"{"hosts": ["a", "x"], "ratio": 1.00, "port": 8080, "a/b": {"c": true}}"#);
+
+        let changes = diff(&from, &to);
+        let lines: Vec<String> = changes.iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "+ /a~1b: {\"c\":true}",
+                "- /hosts/1: \"b\"\n+ /hosts/1: \"x\"",
+                "- /hosts/2: \"c\"",
+                "- /legacy: null",
+                "- /port: 80\n+ /port: 8080",
+            ]
+        );
+
+        let mut value = from.clone();
+        let patch: Vec<Operation> = changes.iter().map(Change::to_operation).collect();
+        apply_patch(&mut value, &patch).unwrap();
+        assert!(cmp::equals(&value, &to));
+
+        let exact = DiffOptions {
+            exact_numbers: true,
+        };
+        let changes = diff_with(&parse("[1.0, 2]"), &parse("[1.00, 2, 3]"), &exact);
+        assert_eq!(
+            changes,
+            vec![
+                Change::Changed {
+                    path: "/0".to_string(),
+                    from: parse("1.0"),
+                    to: parse("1.00"),
+                },
+                Change::Added {
+                    path: "/2".to_string(),
+                    value: parse("3"),
+                },
+            ]
+        );
+
+        assert_eq!(
+            diff(&parse(r#"{"a": [1]}"#), &parse(r#"{"a": [1.0]}"#)),
+            vec![]
+        );
+        assert_eq!(
+            diff(&parse("[1]"), &parse(r#"{"0": 1}"#)),
+            vec![Change::Changed {
+                path: "".to_string(),
+                from: parse("[1]"),
+                to: parse(r#"{"0": 1}"#),
+            }]
+        );
+    }
+}
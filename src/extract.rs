@@ -0,0 +1,344 @@
+/// Extraction of values by JSON Pointers
+///
+/// extract(r#"{"user": {"id": 7, "name": "a"}, "meta": {"ts": 1}, "body": [...]}"#,
+///         &["/user/id", "/meta/ts", "/missing"])
+///
+/// -> [Some(7), Some(1), None]
+///
+/// The input is scanned once. Only the requested values are built, and everything else is
+/// skipped. `extract` takes the first of duplicate keys, and stops scanning as soon as all the
+/// values have been found, so later duplicates and the rest of the input are never seen.
+/// `extract_with_options` handles duplicate keys by `ParserOptions::duplicate_keys` instead, and
+/// scans the whole input unless it is `DuplicateKeys::First`.
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::de::{Deserializer, Value};
+use crate::options::{DuplicateKeys, ParserOptions};
+use crate::pointer::{self, PointerError};
+use crate::token::{self, Token};
+
+#[derive(Eq, PartialEq, Debug)]
+pub enum ExtractError {
+    /// A pointer which is malformed, with its index.
+    Pointer(usize, PointerError),
+    Syntax(token::Error),
+}
+
+impl fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExtractError::Pointer(index, e) => write!(f, "pointer {}: {}", index, e),
+            ExtractError::Syntax(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ExtractError {}
+
+impl From<token::Error> for ExtractError {
+    fn from(e: token::Error) -> ExtractError {
+        ExtractError::Syntax(e)
+    }
+}
+
+/// The values at `pointers` in the same order, or `None` for those which do not exist.
+pub fn extract(input: &str, pointers: &[&str]) -> Result<Vec<Option<Value>>, ExtractError> {
+    let options = ParserOptions::new().duplicate_keys(DuplicateKeys::First);
+    extract_with_options(input, pointers, options)
+}
+
+pub fn extract_with_options(
+    input: &str,
+    pointers: &[&str],
+    options: ParserOptions,
+) -> Result<Vec<Option<Value>>, ExtractError> {
+    let targets = pointers
+        .iter()
+        .enumerate()
+        .map(|(i, p)| pointer::tokens(p).map_err(|e| ExtractError::Pointer(i, e)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut extraction = Extraction {
+        duplicate_keys: options.duplicate_keys,
+        deserializer: Deserializer::new_with_options(input, options),
+        targets,
+        found: vec![None; pointers.len()],
+        remaining: pointers.len(),
+    };
+    if extraction.remaining == 0 {
+        return Ok(vec![]);
+    }
+
+    let active: Vec<usize> = (0..pointers.len()).collect();
+    extraction.walk(0, &active)?;
+
+    if !extraction.done() {
+        let tokenizer = extraction.deserializer.tokenizer();
+        tokenizer.trivia()?;
+        if !tokenizer.is_end() {
+            return Err(ExtractError::Syntax(token::Error::InvalidToken));
+        }
+    }
+
+    Ok(extraction.found)
+}
+
+struct Extraction<'a> {
+    deserializer: Deserializer<'a>,
+    duplicate_keys: DuplicateKeys,
+    /// Reference tokens of each pointer.
+    targets: Vec<Vec<String>>,
+    found: Vec<Option<Value>>,
+    /// The number of targets which have not been found yet.
+    remaining: usize,
+}
+
+impl Extraction<'_> {
+    /// Whether nothing after here can change the values found, so that scanning can stop. A
+    /// later duplicate key may replace them or be an error unless the first one is taken.
+    fn done(&self) -> bool {
+        self.remaining == 0 && self.duplicate_keys == DuplicateKeys::First
+    }
+
+    /// Walks the value at `depth`, the path to which is a prefix of the targets of `active`.
+    fn walk(&mut self, depth: usize, active: &[usize]) -> Result<(), token::Error> {
+        let (here, deeper): (Vec<usize>, Vec<usize>) = active
+            .iter()
+            .copied()
+            .filter(|i| self.found[*i].is_none())
+            .partition(|i| self.targets[*i].len() == depth);
+
+        if !here.is_empty() {
+            let value = self
+                .deserializer
+                .value()?
+                .ok_or(token::Error::InvalidToken)?;
+            for i in deeper {
+                let rest: String = self.targets[i][depth..]
+                    .iter()
+                    .map(|token| format!("/{}", pointer::escape(token)))
+                    .collect();
+                if let Ok(found) = value.pointer(&rest) {
+                    self.resolve(i, found.clone());
+                }
+            }
+            for i in here {
+                self.resolve(i, value.clone());
+            }
+            return Ok(());
+        }
+
+        if deeper.is_empty() {
+            return self.deserializer.skip();
+        }
+
+        let tokenizer = self.deserializer.tokenizer();
+        tokenizer.trivia()?;
+
+        if tokenizer.eat_token(Token::LeftBrace) {
+            tokenizer.trivia()?;
+            if tokenizer.eat_token(Token::RightBrace) {
+                return Ok(());
+            }
+
+            let mut keys = HashSet::new();
+            loop {
+                let tokenizer = self.deserializer.tokenizer();
+                tokenizer.trivia()?;
+                let key = match tokenizer.next()? {
+                    Some(Token::String(key)) => key,
+                    _ => return Err(token::Error::InvalidToken),
+                };
+                tokenizer.trivia()?;
+                if !tokenizer.eat_token(Token::Colon) {
+                    return Err(token::Error::InvalidToken);
+                }
+
+                let next: Vec<usize> = match (keys.insert(key.clone()), self.duplicate_keys) {
+                    (false, DuplicateKeys::Error) => return Err(token::Error::DuplicateKey(key)),
+                    (false, DuplicateKeys::First) => vec![],
+                    _ => deeper
+                        .iter()
+                        .copied()
+                        .filter(|i| self.targets[*i][depth] == key)
+                        .collect(),
+                };
+                // What an earlier member of the same key had is replaced.
+                for i in &next {
+                    self.unresolve(*i);
+                }
+                self.step(depth, &next)?;
+
+                if self.done() || self.deserializer.end_or_comma(Token::RightBrace)? {
+                    return Ok(());
+                }
+            }
+        } else if tokenizer.eat_token(Token::LeftBracket) {
+            tokenizer.trivia()?;
+            if tokenizer.eat_token(Token::RightBracket) {
+                return Ok(());
+            }
+
+            for index in 0.. {
+                let next: Vec<usize> = deeper
+                    .iter()
+                    .copied()
+                    .filter(|i| pointer::parse_index(&self.targets[*i][depth]) == Some(index))
+                    .collect();
+                self.step(depth, &next)?;
+
+                if self.done() || self.deserializer.end_or_comma(Token::RightBracket)? {
+                    return Ok(());
+                }
+            }
+            unreachable!()
+        } else {
+            self.deserializer.skip()
+        }
+    }
+
+    /// Walks into a member or element, or skips it if no targets are under it.
+    fn step(&mut self, depth: usize, next: &[usize]) -> Result<(), token::Error> {
+        if next.is_empty() {
+            self.deserializer.skip()
+        } else {
+            self.walk(depth + 1, next)
+        }
+    }
+
+    fn resolve(&mut self, i: usize, value: Value) {
+        if self.found[i].replace(value).is_none() {
+            self.remaining -= 1;
+        }
+    }
+
+    fn unresolve(&mut self, i: usize) {
+        if self.found[i].take().is_some() {
+            self.remaining += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract() {
+        let input = r#"
+{
+  "body": [{"text": "x\n", "n": 1.50}, [true, null]],
+  "user": {"id": 7, "name": "a", "id": 8},
+  "a/b": {"~": false},
+  "meta": {"ts": "2020-01-01T00:00:00Z"}
+}
+"#;
+        let string = |s: &str| Value::String(s.to_string());
+
+        assert_eq!(
+            extract(
+                input,
+                &[
+                    "/user/id",
+                    "/meta/ts",
+                    "/missing",
+                    "/body/0/n",
+                    "/user/id/x"
+                ]
+            ),
+            Ok(vec![
+                Some(Value::Number("7".to_string())),
+                Some(string("2020-01-01T00:00:00Z")),
+                None,
+                Some(Value::Number("1.50".to_string())),
+                None,
+            ])
+        );
+        assert_eq!(
+            extract(
+                input,
+                &["/body/1", "/body/1/0", "/a~1b/~0", "/body/01", "/body/-"]
+            ),
+            Ok(vec![
+                Some(crate::json!([true, null])),
+                Some(Value::Bool(true)),
+                Some(Value::Bool(false)),
+                None,
+                None,
+            ])
+        );
+        assert_eq!(
+            extract(input, &["", "/user/name"]).unwrap()[0],
+            Deserializer::new_with_options(
+                input,
+                ParserOptions::new().duplicate_keys(DuplicateKeys::First)
+            )
+            .parse()
+            .unwrap()
+        );
+        assert_eq!(extract(input, &[]), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_extract_with_options() {
+        let input = r#"{"a": {"b": 1}, "x": 0, "a": {"c": 2}}"#;
+        let pointers = ["/a/b", "/a/c", "/x"];
+        let number = |n: &str| Some(Value::Number(n.to_string()));
+        let options = |policy| ParserOptions::new().duplicate_keys(policy);
+
+        assert_eq!(
+            extract_with_options(input, &pointers, options(DuplicateKeys::Last)),
+            Ok(vec![None, number("2"), number("0")])
+        );
+        assert_eq!(
+            extract_with_options(input, &pointers, options(DuplicateKeys::First)),
+            Ok(vec![number("1"), None, number("0")])
+        );
+        assert_eq!(
+            extract_with_options(input, &pointers, options(DuplicateKeys::Error)),
+            Err(ExtractError::Syntax(token::Error::DuplicateKey(
+                "a".to_string()
+            )))
+        );
+
+        // Only the first one stops scanning once everything is found.
+        let input = r#"{"a": 1, "a": [}"#;
+        assert_eq!(extract(input, &["/a"]), Ok(vec![number("1")]));
+        assert_eq!(
+            extract_with_options(input, &["/a"], options(DuplicateKeys::Last)),
+            Err(ExtractError::Syntax(token::Error::InvalidToken))
+        );
+
+        assert_eq!(
+            extract_with_options(
+                "{/* c */ \"a\": [1,],}",
+                &["/a"],
+                ParserOptions::new().comments(true).trailing_commas(true)
+            ),
+            Ok(vec![Some(crate::json!([1]))])
+        );
+    }
+
+    #[test]
+    fn test_extract_errors() {
+        assert_eq!(
+            extract("{}", &["/a", "a"]),
+            Err(ExtractError::Pointer(1, PointerError::Malformed))
+        );
+
+        // Scanning stops once everything is found, so the broken tail is never seen.
+        assert_eq!(
+            extract(r#"{"a": 1, "b": [}"#, &["/a"]),
+            Ok(vec![Some(Value::Number("1".to_string()))])
+        );
+        assert_eq!(
+            extract(r#"{"a": 1, "b": [}"#, &["/a", "/c"]),
+            Err(ExtractError::Syntax(token::Error::InvalidToken))
+        );
+        assert_eq!(
+            extract(r#"{"a": 1} 2"#, &["/c"]),
+            Err(ExtractError::Syntax(token::Error::InvalidToken))
+        );
+    }
+}
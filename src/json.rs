@@ -0,0 +1,267 @@
+/// `FromJson` and `ToJson` traits to convert Rust types from and into `Value`
+///
+/// #[derive(FromJson, ToJson)]
+/// struct Config {
+///     host: String,
+///     #[json(rename = "port-number", default)]
+///     port: u16,
+/// }
+///
+/// The derive macros are available with the "derive" feature.
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::BuildHasher;
+
+use crate::convert::ConversionError;
+use crate::de::Value;
+use crate::jsonpath::{self, PathElement};
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum FromJsonError {
+    /// A value of an unexpected type or out of range.
+    Conversion(ConversionError),
+    /// A required member of an object.
+    MissingField(&'static str),
+    /// A string or object key which is not any variant of an enum.
+    UnknownVariant(String),
+    /// An error in a member or element, with the path to it from the value being converted.
+    At(Vec<PathElement>, Box<FromJsonError>),
+}
+
+impl FromJsonError {
+    /// An error for `value` which is not an `expected`.
+    pub fn expected(expected: &'static str, value: &Value) -> FromJsonError {
+        FromJsonError::Conversion(ConversionError::new(expected, value))
+    }
+
+    /// This error in the member `key` of an object.
+    pub fn at_key(self, key: &str) -> FromJsonError {
+        self.at(PathElement::Name(key.to_string()))
+    }
+
+    /// This error in the element at `index` of an array.
+    pub fn at_index(self, index: usize) -> FromJsonError {
+        self.at(PathElement::Index(index))
+    }
+
+    fn at(self, element: PathElement) -> FromJsonError {
+        match self {
+            FromJsonError::At(mut path, e) => {
+                path.insert(0, element);
+                FromJsonError::At(path, e)
+            }
+            e => FromJsonError::At(vec![element], Box::new(e)),
+        }
+    }
+}
+
+impl From<ConversionError> for FromJsonError {
+    fn from(e: ConversionError) -> FromJsonError {
+        FromJsonError::Conversion(e)
+    }
+}
+
+impl fmt::Display for FromJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FromJsonError::Conversion(e) => e.fmt(f),
+            FromJsonError::MissingField(name) => write!(f, "missing field \"{}\"", name),
+            FromJsonError::UnknownVariant(name) => write!(f, "unknown variant \"{}\"", name),
+            FromJsonError::At(path, e) => match &**e {
+                FromJsonError::Conversion(e) => {
+                    write!(f, "invalid type at `{}`: {}", jsonpath::shorthand(path), e)
+                }
+                e => write!(f, "{} at `{}`", e, jsonpath::shorthand(path)),
+            },
+        }
+    }
+}
+
+impl std::error::Error for FromJsonError {}
+
+pub trait FromJson: Sized {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError>;
+
+    /// The value of an absent object member named `field`. Only optional types have one.
+    fn from_missing(field: &'static str) -> Result<Self, FromJsonError> {
+        Err(FromJsonError::MissingField(field))
+    }
+}
+
+pub trait ToJson {
+    fn to_json(&self) -> Value;
+}
+
+impl FromJson for Value {
+    fn from_json(value: &Value) -> Result<Value, FromJsonError> {
+        Ok(value.clone())
+    }
+}
+
+impl ToJson for Value {
+    fn to_json(&self) -> Value {
+        self.clone()
+    }
+}
+
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(value: &Value) -> Result<Option<T>, FromJsonError> {
+        match value {
+            Value::Null => Ok(None),
+            _ => T::from_json(value).map(Some),
+        }
+    }
+
+    fn from_missing(_: &'static str) -> Result<Option<T>, FromJsonError> {
+        Ok(None)
+    }
+}
+
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> Value {
+        self.as_ref().map_or(Value::Null, ToJson::to_json)
+    }
+}
+
+impl<T: FromJson> FromJson for Box<T> {
+    fn from_json(value: &Value) -> Result<Box<T>, FromJsonError> {
+        T::from_json(value).map(Box::new)
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for Box<T> {
+    fn to_json(&self) -> Value {
+        (**self).to_json()
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self) -> Value {
+        (**self).to_json()
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(value: &Value) -> Result<Vec<T>, FromJsonError> {
+        match value {
+            Value::Array(array) => array
+                .iter()
+                .enumerate()
+                .map(|(i, v)| T::from_json(v).map_err(|e| e.at_index(i)))
+                .collect(),
+            _ => Err(FromJsonError::expected("Vec", value)),
+        }
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> Value {
+        self.as_slice().to_json()
+    }
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn to_json(&self) -> Value {
+        Value::Array(self.iter().map(ToJson::to_json).collect())
+    }
+}
+
+impl<T: FromJson, S: BuildHasher + Default> FromJson for HashMap<String, T, S> {
+    fn from_json(value: &Value) -> Result<HashMap<String, T, S>, FromJsonError> {
+        match value {
+            Value::Object(object) => object
+                .iter()
+                .map(|(k, v)| Ok((k.clone(), T::from_json(v).map_err(|e| e.at_key(k))?)))
+                .collect(),
+            _ => Err(FromJsonError::expected("HashMap", value)),
+        }
+    }
+}
+
+impl<T: ToJson, S: BuildHasher> ToJson for HashMap<String, T, S> {
+    fn to_json(&self) -> Value {
+        Value::Object(self.iter().map(|(k, v)| (k.clone(), v.to_json())).collect())
+    }
+}
+
+impl ToJson for str {
+    fn to_json(&self) -> Value {
+        Value::from(self)
+    }
+}
+
+/// Scalars convert as `TryFrom<Value>` and `From` do, so floats which are NaN or infinite panic.
+macro_rules! scalar {
+    ($($t:ident)*) => {
+        $(
+            impl FromJson for $t {
+                fn from_json(value: &Value) -> Result<$t, FromJsonError> {
+                    Ok($t::try_from(value.clone())?)
+                }
+            }
+
+            impl ToJson for $t {
+                fn to_json(&self) -> Value {
+                    Value::from(self.clone())
+                }
+            }
+        )*
+    };
+}
+
+scalar!(bool String i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize f32 f64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_json() {
+        let value = crate::json!({"a": [1, null], "b": []});
+        let map: HashMap<String, Vec<Option<u8>>> = FromJson::from_json(&value).unwrap();
+        assert_eq!(map["a"], vec![Some(1), None]);
+        assert_eq!(map.to_json(), value);
+
+        assert_eq!(Option::<u8>::from_missing("a"), Ok(None));
+        assert_eq!(u8::from_missing("a"), Err(FromJsonError::MissingField("a")));
+        assert_eq!(
+            Vec::<u8>::from_json(&crate::json!([1, true]))
+                .unwrap_err()
+                .to_string(),
+            "invalid type at `$[1]`: expected u8, found boolean true"
+        );
+
+        let value = crate::json!({"servers": [{"ports": []}, {"ports": [80, "x"]}]});
+        let error =
+            HashMap::<String, Vec<HashMap<String, Vec<u16>>>>::from_json(&value).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid type at `$.servers[1].ports[1]`: expected u16, found string"
+        );
+        assert_eq!(
+            FromJsonError::MissingField("port")
+                .at_index(3)
+                .at_key("a b")
+                .to_string(),
+            "missing field \"port\" at `$['a b'][3]`"
+        );
+        assert_eq!("a".to_json(), Value::String("a".to_string()));
+
+        let value = vec![0.5, -1e-7].to_json();
+        assert_eq!(value.to_string(), "[0.5,-1e-7]");
+        assert_eq!(Vec::<f64>::from_json(&value), Ok(vec![0.5, -1e-7]));
+        assert_eq!(
+            Vec::<f32>::from_json(&crate::json!([1, "x"]))
+                .unwrap_err()
+                .to_string(),
+            "invalid type at `$[1]`: expected f32, found string"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Infinity is not a JSON number; use `Value::from_f32`")]
+    fn test_to_json_infinity() {
+        let _ = vec![0.5, f32::INFINITY].to_json();
+    }
+}
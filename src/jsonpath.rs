@@ -0,0 +1,1370 @@
+/// JSONPath (RFC 9535)
+///
+/// - $.store.book[0].title
+/// - $..author
+/// - $.items[?@.status == 'failed'].id
+/// - $[1:5:2]
+/// - $[?length(@.tags) > 2 && match(@.name, '[a-z]+')]
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::cmp;
+use crate::de::Value;
+use crate::number::Decimal;
+use crate::pointer;
+use crate::regex::Regex;
+
+/// I-JSON integer range, which indices and slice bounds are limited to.
+const MAX_INT: i64 = (1 << 53) - 1;
+
+#[derive(Eq, PartialEq, Debug)]
+pub enum JsonPathError {
+    /// Unexpected character at the byte offset.
+    Syntax(usize),
+    UnknownFunction(String),
+    /// Ill-typed expression at the byte offset, e.g. a non-singular query in a comparison.
+    Type(usize),
+}
+
+impl fmt::Display for JsonPathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JsonPathError::Syntax(offset) => write!(f, "syntax error at offset {}", offset),
+            JsonPathError::UnknownFunction(name) => write!(f, "unknown function \"{}\"", name),
+            JsonPathError::Type(offset) => write!(f, "type error at offset {}", offset),
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum PathElement {
+    Name(String),
+    Index(usize),
+}
+
+/// A value selected by a query, and where it is.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Node<'a> {
+    pub location: Vec<PathElement>,
+    pub value: &'a Value,
+}
+
+impl Node<'_> {
+    /// Normalized path, e.g. `$['items'][0]['id']`.
+    pub fn path(&self) -> String {
+        let mut path = "$".to_string();
+
+        for element in self.location.iter() {
+            match element {
+                PathElement::Name(name) => push_bracketed(&mut path, name),
+                PathElement::Index(index) => path.push_str(&format!("[{}]", index)),
+            }
+        }
+
+        path
+    }
+
+    /// JSON Pointer to the value, e.g. `/items/0/id`.
+    pub fn pointer(&self) -> String {
+        self.location
+            .iter()
+            .map(|element| match element {
+                PathElement::Name(name) => format!("/{}", pointer::escape(name)),
+                PathElement::Index(index) => format!("/{}", index),
+            })
+            .collect()
+    }
+
+    fn child<'b>(&self, element: PathElement, value: &'b Value) -> Node<'b> {
+        let mut location = self.location.clone();
+        location.push(element);
+
+        Node { location, value }
+    }
+}
+
+/// Path in the shorthand notation for messages, e.g. `$.items[0].id`. Names which are not
+/// identifiers are bracketed as in `Node::path`, e.g. `$['a b']`.
+pub(crate) fn shorthand(location: &[PathElement]) -> String {
+    let mut path = "$".to_string();
+
+    for element in location.iter() {
+        match element {
+            PathElement::Name(name) if is_identifier(name) => {
+                path.push('.');
+                path.push_str(name);
+            }
+            PathElement::Name(name) => push_bracketed(&mut path, name),
+            PathElement::Index(index) => path.push_str(&format!("[{}]", index)),
+        }
+    }
+
+    path
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Pushes `['name']` with `name` escaped.
+fn push_bracketed(path: &mut String, name: &str) {
+    path.push_str("['");
+    for c in name.chars() {
+        match c {
+            '\x08' => path.push_str("\\b"),
+            '\x0C' => path.push_str("\\f"),
+            '\n' => path.push_str("\\n"),
+            '\r' => path.push_str("\\r"),
+            '\t' => path.push_str("\\t"),
+            '\'' => path.push_str("\\'"),
+            '\\' => path.push_str("\\\\"),
+            c if c < ' ' => path.push_str(&format!("\\u{:04x}", c as u32)),
+            c => path.push(c),
+        }
+    }
+    path.push_str("']");
+}
+
+/// A compiled query.
+#[derive(Debug)]
+pub struct JsonPath {
+    query: Query,
+}
+
+impl JsonPath {
+    pub fn parse(query: &str) -> Result<JsonPath, JsonPathError> {
+        let mut parser = Parser {
+            input: query,
+            pos: 0,
+        };
+
+        if !parser.eatc('$') {
+            return Err(parser.error());
+        }
+        let segments = parser.segments()?;
+
+        if parser.pos != query.len() {
+            return Err(parser.error());
+        }
+
+        Ok(JsonPath {
+            query: Query {
+                relative: false,
+                segments,
+            },
+        })
+    }
+
+    /// Nodes selected from `root`, in the order of RFC 9535. Members of an object are visited in
+    /// an unspecified order.
+    pub fn query<'a>(&self, root: &'a Value) -> Vec<Node<'a>> {
+        self.query.select(root, root)
+    }
+}
+
+impl Value {
+    /// Shorthand of `JsonPath::parse(path)?.query(self)`.
+    pub fn query(&self, path: &str) -> Result<Vec<Node<'_>>, JsonPathError> {
+        Ok(JsonPath::parse(path)?.query(self))
+    }
+}
+
+#[derive(Debug)]
+struct Query {
+    /// Starts with `@` instead of `$`.
+    relative: bool,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug)]
+enum Segment {
+    Child(Vec<Selector>),
+    Descendant(Vec<Selector>),
+}
+
+#[derive(Debug)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, Option<i64>),
+    Filter(Logical),
+}
+
+#[derive(Debug)]
+enum Logical {
+    Or(Vec<Logical>),
+    And(Vec<Logical>),
+    Not(Box<Logical>),
+    Comparison(Comparable, Op, Comparable),
+    /// Existence of nodes.
+    Query(Query),
+    Function(Function),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug)]
+enum Comparable {
+    Literal(Value),
+    /// Singular query.
+    Query(Query),
+    Function(Function),
+}
+
+#[derive(Debug)]
+struct Function {
+    name: FunctionName,
+    args: Vec<Argument>,
+    /// The pattern of `match` or `search` compiled at parse time, if it is a string literal.
+    regex: Option<Regex>,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+enum FunctionName {
+    Length,
+    Count,
+    Match,
+    Search,
+    Value,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+enum Type {
+    Value,
+    Logical,
+    Nodes,
+}
+
+#[derive(Debug)]
+enum Argument {
+    Literal(Value),
+    Query(Query),
+    Function(Function),
+}
+
+impl FunctionName {
+    fn from_name(name: &str) -> Option<FunctionName> {
+        match name {
+            "length" => Some(FunctionName::Length),
+            "count" => Some(FunctionName::Count),
+            "match" => Some(FunctionName::Match),
+            "search" => Some(FunctionName::Search),
+            "value" => Some(FunctionName::Value),
+            _ => None,
+        }
+    }
+
+    fn params(self) -> &'static [Type] {
+        match self {
+            FunctionName::Length => &[Type::Value],
+            FunctionName::Count | FunctionName::Value => &[Type::Nodes],
+            FunctionName::Match | FunctionName::Search => &[Type::Value, Type::Value],
+        }
+    }
+
+    fn result(self) -> Type {
+        match self {
+            FunctionName::Length | FunctionName::Count | FunctionName::Value => Type::Value,
+            FunctionName::Match | FunctionName::Search => Type::Logical,
+        }
+    }
+}
+
+impl Query {
+    /// Only name and index selectors in child segments.
+    fn is_singular(&self) -> bool {
+        self.segments.iter().all(|segment| match segment {
+            Segment::Child(selectors) => {
+                selectors.len() == 1
+                    && matches!(selectors[0], Selector::Name(_) | Selector::Index(_))
+            }
+            Segment::Descendant(_) => false,
+        })
+    }
+
+    fn select<'a>(&self, current: &'a Value, root: &'a Value) -> Vec<Node<'a>> {
+        let start = if self.relative { current } else { root };
+        let mut nodes = vec![Node {
+            location: vec![],
+            value: start,
+        }];
+
+        for segment in self.segments.iter() {
+            let mut selected = vec![];
+
+            for node in nodes.iter() {
+                match segment {
+                    Segment::Child(selectors) => {
+                        for selector in selectors.iter() {
+                            selector.select(node, root, &mut selected);
+                        }
+                    }
+                    Segment::Descendant(selectors) => {
+                        let mut descendants = vec![];
+                        descend(node.clone(), &mut descendants);
+
+                        for descendant in descendants.iter() {
+                            for selector in selectors.iter() {
+                                selector.select(descendant, root, &mut selected);
+                            }
+                        }
+                    }
+                }
+            }
+
+            nodes = selected;
+        }
+
+        nodes
+    }
+}
+
+/// The node and all of its descendants, where parents come before their children.
+fn descend<'a>(node: Node<'a>, nodes: &mut Vec<Node<'a>>) {
+    let value = node.value;
+    nodes.push(node);
+    let node = &nodes[nodes.len() - 1];
+
+    let children: Vec<Node<'a>> = match value {
+        Value::Array(array) => array
+            .iter()
+            .enumerate()
+            .map(|(i, v)| node.child(PathElement::Index(i), v))
+            .collect(),
+        Value::Object(object) => object
+            .iter()
+            .map(|(k, v)| node.child(PathElement::Name(k.clone()), v))
+            .collect(),
+        _ => vec![],
+    };
+
+    for child in children {
+        descend(child, nodes);
+    }
+}
+
+impl Selector {
+    fn select<'a>(&self, node: &Node<'a>, root: &'a Value, selected: &mut Vec<Node<'a>>) {
+        match (self, node.value) {
+            (Selector::Name(name), Value::Object(object)) => {
+                if let Some((key, value)) = object.get_key_value(name) {
+                    selected.push(node.child(PathElement::Name(key.clone()), value));
+                }
+            }
+            (Selector::Wildcard, Value::Array(array)) => {
+                for (i, value) in array.iter().enumerate() {
+                    selected.push(node.child(PathElement::Index(i), value));
+                }
+            }
+            (Selector::Wildcard, Value::Object(object)) => {
+                for (key, value) in object.iter() {
+                    selected.push(node.child(PathElement::Name(key.clone()), value));
+                }
+            }
+            (Selector::Index(index), Value::Array(array)) => {
+                let len = array.len() as i64;
+                let index = if *index < 0 { len + index } else { *index };
+
+                if 0 <= index && index < len {
+                    let index = index as usize;
+                    selected.push(node.child(PathElement::Index(index), &array[index]));
+                }
+            }
+            (Selector::Slice(start, end, step), Value::Array(array)) => {
+                for index in slice(array.len() as i64, *start, *end, *step) {
+                    let index = index as usize;
+                    selected.push(node.child(PathElement::Index(index), &array[index]));
+                }
+            }
+            (Selector::Filter(logical), Value::Array(array)) => {
+                for (i, value) in array.iter().enumerate() {
+                    if logical.test(value, root) {
+                        selected.push(node.child(PathElement::Index(i), value));
+                    }
+                }
+            }
+            (Selector::Filter(logical), Value::Object(object)) => {
+                for (key, value) in object.iter() {
+                    if logical.test(value, root) {
+                        selected.push(node.child(PathElement::Name(key.clone()), value));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Indices selected by `[start:end:step]` from an array of `len` elements.
+fn slice(len: i64, start: Option<i64>, end: Option<i64>, step: Option<i64>) -> Vec<i64> {
+    let step = step.unwrap_or(1);
+    let normalize = |i: i64| if i >= 0 { i } else { len + i };
+    let mut indices = vec![];
+
+    if step > 0 {
+        let lower = normalize(start.unwrap_or(0)).max(0).min(len);
+        let upper = normalize(end.unwrap_or(len)).max(0).min(len);
+
+        let mut i = lower;
+        while i < upper {
+            indices.push(i);
+            i += step;
+        }
+    } else if step < 0 {
+        let upper = start.map_or(len - 1, normalize).max(-1).min(len - 1);
+        let lower = end.map_or(-1, normalize).max(-1).min(len - 1);
+
+        let mut i = upper;
+        while lower < i {
+            indices.push(i);
+            i += step;
+        }
+    }
+
+    indices
+}
+
+impl Logical {
+    fn test(&self, current: &Value, root: &Value) -> bool {
+        match self {
+            Logical::Or(logicals) => logicals.iter().any(|l| l.test(current, root)),
+            Logical::And(logicals) => logicals.iter().all(|l| l.test(current, root)),
+            Logical::Not(logical) => !logical.test(current, root),
+            Logical::Comparison(left, op, right) => {
+                let left = left.evaluate(current, root);
+                let right = right.evaluate(current, root);
+                compare(left.as_deref(), *op, right.as_deref())
+            }
+            Logical::Query(query) => !query.select(current, root).is_empty(),
+            Logical::Function(function) => function.test(current, root),
+        }
+    }
+}
+
+impl Comparable {
+    /// `None` is "Nothing", the result of an empty node list.
+    fn evaluate<'a>(&'a self, current: &'a Value, root: &'a Value) -> Option<Cow<'a, Value>> {
+        match self {
+            Comparable::Literal(value) => Some(Cow::Borrowed(value)),
+            Comparable::Query(query) => singular(query, current, root),
+            Comparable::Function(function) => function.evaluate(current, root),
+        }
+    }
+}
+
+fn singular<'a>(query: &Query, current: &'a Value, root: &'a Value) -> Option<Cow<'a, Value>> {
+    let mut nodes = query.select(current, root);
+
+    match nodes.len() {
+        1 => nodes.pop().map(|node| Cow::Borrowed(node.value)),
+        _ => None,
+    }
+}
+
+impl Function {
+    /// Result of a function of `Type::Value`.
+    fn evaluate<'a>(&'a self, current: &'a Value, root: &'a Value) -> Option<Cow<'a, Value>> {
+        match self.name {
+            FunctionName::Length => {
+                let len = match self.args[0].value(current, root)?.as_ref() {
+                    Value::String(s) => s.chars().count(),
+                    Value::Array(array) => array.len(),
+                    Value::Object(object) => object.len(),
+                    _ => return None,
+                };
+                Some(Cow::Owned(Value::Number(len.to_string())))
+            }
+            FunctionName::Count => {
+                let count = self.args[0].nodes(current, root).len();
+                Some(Cow::Owned(Value::Number(count.to_string())))
+            }
+            FunctionName::Value => {
+                let mut nodes = self.args[0].nodes(current, root);
+                match nodes.len() {
+                    1 => nodes.pop().map(|node| Cow::Borrowed(node.value)),
+                    _ => None,
+                }
+            }
+            FunctionName::Match | FunctionName::Search => None,
+        }
+    }
+
+    /// Result of a function of `Type::Logical`.
+    fn test(&self, current: &Value, root: &Value) -> bool {
+        let input = match self.args[0].value(current, root) {
+            Some(input) => input,
+            None => return false,
+        };
+        let input = match input.as_ref() {
+            Value::String(input) => input,
+            _ => return false,
+        };
+
+        let compiled;
+        let regex = match &self.args[1] {
+            Argument::Literal(_) => self.regex.as_ref(),
+            pattern => {
+                compiled = match pattern.value(current, root).as_deref() {
+                    Some(Value::String(pattern)) => Regex::new(pattern),
+                    _ => None,
+                };
+                compiled.as_ref()
+            }
+        };
+
+        match regex {
+            Some(regex) if self.name == FunctionName::Match => regex.is_match(input),
+            Some(regex) => regex.search(input),
+            None => false,
+        }
+    }
+}
+
+impl Argument {
+    fn value<'a>(&'a self, current: &'a Value, root: &'a Value) -> Option<Cow<'a, Value>> {
+        match self {
+            Argument::Literal(value) => Some(Cow::Borrowed(value)),
+            Argument::Query(query) => singular(query, current, root),
+            Argument::Function(function) => function.evaluate(current, root),
+        }
+    }
+
+    fn nodes<'a>(&self, current: &'a Value, root: &'a Value) -> Vec<Node<'a>> {
+        match self {
+            Argument::Query(query) => query.select(current, root),
+            _ => vec![],
+        }
+    }
+}
+
+fn compare(left: Option<&Value>, op: Op, right: Option<&Value>) -> bool {
+    match op {
+        Op::Eq => equals(left, right),
+        Op::Ne => !equals(left, right),
+        Op::Lt => less(left, right),
+        Op::Le => less(left, right) || equals(left, right),
+        Op::Gt => less(right, left),
+        Op::Ge => less(right, left) || equals(left, right),
+    }
+}
+
+fn equals(left: Option<&Value>, right: Option<&Value>) -> bool {
+    match (left, right) {
+        (None, None) => true,
+        (Some(left), Some(right)) => cmp::equals(left, right),
+        _ => false,
+    }
+}
+
+fn less(left: Option<&Value>, right: Option<&Value>) -> bool {
+    match (left, right) {
+        (Some(Value::Number(l)), Some(Value::Number(r))) => {
+            match (Decimal::parse(l), Decimal::parse(r)) {
+                (Some(l), Some(r)) => l < r,
+                _ => false,
+            }
+        }
+        (Some(Value::String(l)), Some(Value::String(r))) => l < r,
+        _ => false,
+    }
+}
+
+/// An operand of a comparison or a function argument, before type checking.
+enum Operand {
+    Literal(Value),
+    Query(Query),
+    Function(Function),
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self) -> JsonPathError {
+        JsonPathError::Syntax(self.pos)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn one(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eatc(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eats(&mut self, s: &str) -> bool {
+        if self.input[self.pos..].starts_with(s) {
+            self.pos += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), JsonPathError> {
+        if self.eatc(c) {
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    /// " ", "\t", "\n" and "\r"
+    fn blank(&mut self) {
+        while let Some(' ') | Some('\t') | Some('\n') | Some('\r') = self.peek() {
+            self.one();
+        }
+    }
+
+    fn segments(&mut self) -> Result<Vec<Segment>, JsonPathError> {
+        let mut segments = vec![];
+
+        loop {
+            let save = self.pos;
+            self.blank();
+
+            if self.eats("..") {
+                let selectors = match self.peek() {
+                    Some('[') => self.bracketed()?,
+                    Some('*') => {
+                        self.one();
+                        vec![Selector::Wildcard]
+                    }
+                    _ => vec![Selector::Name(self.member_name()?)],
+                };
+                segments.push(Segment::Descendant(selectors));
+            } else if self.eatc('.') {
+                let selector = match self.peek() {
+                    Some('*') => {
+                        self.one();
+                        Selector::Wildcard
+                    }
+                    _ => Selector::Name(self.member_name()?),
+                };
+                segments.push(Segment::Child(vec![selector]));
+            } else if self.peek() == Some('[') {
+                segments.push(Segment::Child(self.bracketed()?));
+            } else {
+                self.pos = save;
+                return Ok(segments);
+            }
+        }
+    }
+
+    /// ALPHA / "_" / non-ASCII, then DIGIT too
+    fn member_name(&mut self) -> Result<String, JsonPathError> {
+        let start = self.pos;
+
+        match self.peek() {
+            Some(c) if c.is_ascii_alphabetic() || c == '_' || !c.is_ascii() => {}
+            _ => return Err(self.error()),
+        }
+        while let Some(c) = self.peek() {
+            if !(c.is_ascii_alphanumeric() || c == '_' || !c.is_ascii()) {
+                break;
+            }
+            self.one();
+        }
+
+        Ok(self.input[start..self.pos].to_string())
+    }
+
+    fn bracketed(&mut self) -> Result<Vec<Selector>, JsonPathError> {
+        self.expect('[')?;
+        self.blank();
+
+        let mut selectors = vec![self.selector()?];
+        loop {
+            self.blank();
+            if self.eatc(']') {
+                return Ok(selectors);
+            }
+
+            self.expect(',')?;
+            self.blank();
+            selectors.push(self.selector()?);
+        }
+    }
+
+    fn selector(&mut self) -> Result<Selector, JsonPathError> {
+        match self.peek() {
+            Some('\'') | Some('"') => Ok(Selector::Name(self.string()?)),
+            Some('*') => {
+                self.one();
+                Ok(Selector::Wildcard)
+            }
+            Some('?') => {
+                self.one();
+                self.blank();
+                Ok(Selector::Filter(self.logical_or()?))
+            }
+            _ => {
+                let start = self.integer()?;
+                let save = self.pos;
+                self.blank();
+
+                if !self.eatc(':') {
+                    self.pos = save;
+                    return start.map(Selector::Index).ok_or_else(|| self.error());
+                }
+
+                self.blank();
+                let end = self.integer()?;
+                self.blank();
+
+                let mut step = None;
+                if self.eatc(':') {
+                    self.blank();
+                    step = self.integer()?;
+                }
+
+                Ok(Selector::Slice(start, end, step))
+            }
+        }
+    }
+
+    /// "0" / ["-"] DIGIT1 *DIGIT, if any.
+    fn integer(&mut self) -> Result<Option<i64>, JsonPathError> {
+        let start = self.pos;
+        self.eatc('-');
+
+        let digits = self.pos;
+        while let Some(c) = self.peek() {
+            if !c.is_ascii_digit() {
+                break;
+            }
+            self.one();
+        }
+
+        let lexeme = &self.input[start..self.pos];
+        let digits = &self.input[digits..self.pos];
+        if lexeme.is_empty() {
+            return Ok(None);
+        }
+        if digits.is_empty() || (digits.starts_with('0') && lexeme != "0") {
+            self.pos = start;
+            return Err(self.error());
+        }
+
+        match lexeme.parse::<i64>() {
+            Ok(n) if (-MAX_INT..=MAX_INT).contains(&n) => Ok(Some(n)),
+            _ => {
+                self.pos = start;
+                Err(self.error())
+            }
+        }
+    }
+
+    /// '...' or "..."
+    fn string(&mut self) -> Result<String, JsonPathError> {
+        let quote = self.one().ok_or_else(|| self.error())?;
+        let mut val = "".to_string();
+
+        loop {
+            match self.one() {
+                Some(c) if c == quote => return Ok(val),
+                Some('\\') => {
+                    let c = match self.one() {
+                        Some('b') => '\x08',
+                        Some('f') => '\x0C',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('/') => '/',
+                        Some('\\') => '\\',
+                        Some(c) if c == quote => c,
+                        Some('u') => self.unicode()?,
+                        _ => return Err(self.error()),
+                    };
+                    val.push(c);
+                }
+                Some(c) if c >= ' ' => val.push(c),
+                _ => return Err(self.error()),
+            }
+        }
+    }
+
+    /// Assumes that "\u" has been already eaten.
+    fn unicode(&mut self) -> Result<char, JsonPathError> {
+        let high = self.hex4()?;
+
+        if !(0xD800..0xDC00).contains(&high) {
+            return std::char::from_u32(high).ok_or_else(|| self.error());
+        }
+
+        if !self.eats("\\u") {
+            return Err(self.error());
+        }
+        let low = self.hex4()?;
+        if !(0xDC00..0xE000).contains(&low) {
+            return Err(self.error());
+        }
+
+        std::char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+            .ok_or_else(|| self.error())
+    }
+
+    fn hex4(&mut self) -> Result<u32, JsonPathError> {
+        let hex = self.input.get(self.pos..self.pos + 4).unwrap_or("");
+
+        match u32::from_str_radix(hex, 16) {
+            Ok(n) if hex.bytes().all(|b| b.is_ascii_hexdigit()) => {
+                self.pos += 4;
+                Ok(n)
+            }
+            _ => Err(self.error()),
+        }
+    }
+
+    fn logical_or(&mut self) -> Result<Logical, JsonPathError> {
+        let mut logicals = vec![self.logical_and()?];
+
+        loop {
+            let save = self.pos;
+            self.blank();
+            if !self.eats("||") {
+                self.pos = save;
+                break;
+            }
+            self.blank();
+            logicals.push(self.logical_and()?);
+        }
+
+        match logicals.len() {
+            1 => Ok(logicals.remove(0)),
+            _ => Ok(Logical::Or(logicals)),
+        }
+    }
+
+    fn logical_and(&mut self) -> Result<Logical, JsonPathError> {
+        let mut logicals = vec![self.basic()?];
+
+        loop {
+            let save = self.pos;
+            self.blank();
+            if !self.eats("&&") {
+                self.pos = save;
+                break;
+            }
+            self.blank();
+            logicals.push(self.basic()?);
+        }
+
+        match logicals.len() {
+            1 => Ok(logicals.remove(0)),
+            _ => Ok(Logical::And(logicals)),
+        }
+    }
+
+    /// Parenthesized, comparison or test expression.
+    fn basic(&mut self) -> Result<Logical, JsonPathError> {
+        let not = self.eatc('!');
+        if not {
+            self.blank();
+        }
+
+        let logical = if self.eatc('(') {
+            self.blank();
+            let logical = self.logical_or()?;
+            self.blank();
+            self.expect(')')?;
+            logical
+        } else {
+            let start = self.pos;
+            let operand = self.operand()?;
+
+            let save = self.pos;
+            self.blank();
+            match self.comparison_op() {
+                Some(op) if !not => {
+                    self.blank();
+                    let right_start = self.pos;
+                    let right = self.operand()?;
+
+                    Logical::Comparison(
+                        comparable(operand, start)?,
+                        op,
+                        comparable(right, right_start)?,
+                    )
+                }
+                _ => {
+                    self.pos = save;
+
+                    match operand {
+                        Operand::Query(query) => Logical::Query(query),
+                        Operand::Function(function) if function.name.result() != Type::Value => {
+                            Logical::Function(function)
+                        }
+                        _ => return Err(JsonPathError::Type(start)),
+                    }
+                }
+            }
+        };
+
+        if not {
+            Ok(Logical::Not(Box::new(logical)))
+        } else {
+            Ok(logical)
+        }
+    }
+
+    fn comparison_op(&mut self) -> Option<Op> {
+        let ops = [
+            ("==", Op::Eq),
+            ("!=", Op::Ne),
+            ("<=", Op::Le),
+            (">=", Op::Ge),
+            ("<", Op::Lt),
+            (">", Op::Gt),
+        ];
+
+        ops.iter().find(|(s, _)| self.eats(s)).map(|(_, op)| *op)
+    }
+
+    /// Literal, query or function expression.
+    fn operand(&mut self) -> Result<Operand, JsonPathError> {
+        match self.peek() {
+            Some('@') | Some('$') => {
+                let relative = self.one() == Some('@');
+                let segments = self.segments()?;
+                Ok(Operand::Query(Query { relative, segments }))
+            }
+            Some('\'') | Some('"') => Ok(Operand::Literal(Value::String(self.string()?))),
+            Some(c) if c == '-' || c.is_ascii_digit() => Ok(Operand::Literal(self.number()?)),
+            Some(c) if c.is_ascii_lowercase() => {
+                let start = self.pos;
+                while let Some(c) = self.peek() {
+                    if !(c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+                        break;
+                    }
+                    self.one();
+                }
+                let name = &self.input[start..self.pos];
+
+                if self.peek() == Some('(') {
+                    return self.function(name, start).map(Operand::Function);
+                }
+
+                match name {
+                    "true" => Ok(Operand::Literal(Value::Bool(true))),
+                    "false" => Ok(Operand::Literal(Value::Bool(false))),
+                    "null" => Ok(Operand::Literal(Value::Null)),
+                    _ => {
+                        self.pos = start;
+                        Err(self.error())
+                    }
+                }
+            }
+            _ => Err(self.error()),
+        }
+    }
+
+    /// (int / "-0") [ frac ] [ exp ]
+    fn number(&mut self) -> Result<Value, JsonPathError> {
+        let start = self.pos;
+
+        self.eatc('-');
+        while let Some(c) = self.peek() {
+            if !(c.is_ascii_alphanumeric() || c == '.' || c == '+' || c == '-') {
+                break;
+            }
+            self.one();
+        }
+
+        let lexeme = &self.input[start..self.pos];
+        match Decimal::parse(lexeme) {
+            Some(_) => Ok(Value::Number(lexeme.to_string())),
+            None => {
+                self.pos = start;
+                Err(self.error())
+            }
+        }
+    }
+
+    /// Assumes that the name has been already eaten.
+    fn function(&mut self, name: &str, start: usize) -> Result<Function, JsonPathError> {
+        let name = FunctionName::from_name(name)
+            .ok_or_else(|| JsonPathError::UnknownFunction(name.to_string()))?;
+
+        self.expect('(')?;
+        self.blank();
+
+        let mut args = vec![];
+        if !self.eatc(')') {
+            loop {
+                let arg_start = self.pos;
+                let operand = self.operand()?;
+                let param = name.params().get(args.len());
+                args.push(argument(operand, param, arg_start)?);
+
+                self.blank();
+                if self.eatc(')') {
+                    break;
+                }
+                self.expect(',')?;
+                self.blank();
+            }
+        }
+
+        if args.len() != name.params().len() {
+            return Err(JsonPathError::Type(start));
+        }
+
+        let regex = match (name, args.get(1)) {
+            (FunctionName::Match, Some(Argument::Literal(Value::String(pattern))))
+            | (FunctionName::Search, Some(Argument::Literal(Value::String(pattern)))) => {
+                Regex::new(pattern)
+            }
+            _ => None,
+        };
+
+        Ok(Function { name, args, regex })
+    }
+}
+
+fn comparable(operand: Operand, start: usize) -> Result<Comparable, JsonPathError> {
+    match operand {
+        Operand::Literal(value) => Ok(Comparable::Literal(value)),
+        Operand::Query(query) if query.is_singular() => Ok(Comparable::Query(query)),
+        Operand::Function(function) if function.name.result() == Type::Value => {
+            Ok(Comparable::Function(function))
+        }
+        _ => Err(JsonPathError::Type(start)),
+    }
+}
+
+fn argument(
+    operand: Operand,
+    param: Option<&Type>,
+    start: usize,
+) -> Result<Argument, JsonPathError> {
+    match (param, operand) {
+        (Some(Type::Value), Operand::Literal(value)) => Ok(Argument::Literal(value)),
+        (Some(Type::Value), Operand::Query(query)) if query.is_singular() => {
+            Ok(Argument::Query(query))
+        }
+        (Some(Type::Value), Operand::Function(function))
+            if function.name.result() == Type::Value =>
+        {
+            Ok(Argument::Function(function))
+        }
+        (Some(Type::Nodes), Operand::Query(query)) => Ok(Argument::Query(query)),
+        _ => Err(JsonPathError::Type(start)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::de::parse;
+
+    /// Normalized paths of the selected nodes, sorted as object members have no order.
+    fn paths(value: &Value, query: &str) -> Vec<String> {
+        let mut paths: Vec<String> = value
+            .query(query)
+            .unwrap()
+            .iter()
+            .map(|node| node.path())
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    /// Normalized paths of the selected nodes in order.
+    fn ordered_paths(value: &Value, query: &str) -> Vec<String> {
+        value
+            .query(query)
+            .unwrap()
+            .iter()
+            .map(|node| node.path())
+            .collect()
+    }
+
+    fn bookstore() -> Value {
+        // RFC 9535 section 1.5
+        parse(
+            r#"
+{ "store": {
+    "book": [
+      { "category": "reference",
+        "author": "Nigel Rees",
+        "title": "Sayings of the Century",
+        "price": 8.95
+      },
+      { "category": "fiction",
+        "author": "Evelyn Waugh",
+        "title": "Sword of Honour",
+        "price": 12.99
+      },
+      { "category": "fiction",
+        "author": "Herman Melville",
+        "title": "Moby Dick",
+        "isbn": "0-553-21311-3",
+        "price": 8.99
+      },
+      { "category": "fiction",
+        "author": "J. R. R. Tolkien",
+        "title": "The Lord of the Rings",
+        "isbn": "0-395-19395-8",
+        "price": 22.99
+      }
+    ],
+    "bicycle": {
+      "color": "red",
+      "price": 399
+    }
+  }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn test_jsonpath_bookstore() {
+        let value = bookstore();
+
+        assert_eq!(
+            ordered_paths(&value, "$.store.book[*].author"),
+            vec![
+                "$['store']['book'][0]['author']",
+                "$['store']['book'][1]['author']",
+                "$['store']['book'][2]['author']",
+                "$['store']['book'][3]['author']",
+            ]
+        );
+        assert_eq!(paths(&value, "$..author").len(), 4);
+        assert_eq!(paths(&value, "$.store.*").len(), 2);
+        assert_eq!(paths(&value, "$.store..price").len(), 5);
+        assert_eq!(
+            ordered_paths(&value, "$..book[2]"),
+            vec!["$['store']['book'][2]"]
+        );
+        assert_eq!(
+            ordered_paths(&value, "$..book[-1]"),
+            vec!["$['store']['book'][3]"]
+        );
+        assert_eq!(
+            ordered_paths(&value, "$..book[0,1]"),
+            vec!["$['store']['book'][0]", "$['store']['book'][1]"]
+        );
+        assert_eq!(
+            ordered_paths(&value, "$..book[:2]"),
+            vec!["$['store']['book'][0]", "$['store']['book'][1]"]
+        );
+        assert_eq!(
+            ordered_paths(&value, "$..book[?@.isbn]"),
+            vec!["$['store']['book'][2]", "$['store']['book'][3]"]
+        );
+        assert_eq!(
+            ordered_paths(&value, "$..book[?@.price<10]"),
+            vec!["$['store']['book'][0]", "$['store']['book'][2]"]
+        );
+        assert_eq!(paths(&value, "$..*").len(), 27);
+    }
+
+    #[test]
+    fn test_jsonpath_filter() {
+        let value = parse(
+            r#"
+{
+  "items": [
+    {"id": 1, "status": "failed", "tags": ["a", "b"]},
+    {"id": 2, "status": "ok", "tags": []},
+    {"id": 3, "status": "failed", "tags": ["c"], "retry": 2.0},
+    {"id": 4, "retry": 2}
+  ]
+}
+"#,
+        );
+
+        let ids = |query: &str| -> Vec<Value> {
+            value
+                .query(query)
+                .unwrap()
+                .into_iter()
+                .map(|node| node.value.clone())
+                .collect()
+        };
+        let numbers = |ns: &[&str]| -> Vec<Value> {
+            ns.iter().map(|n| Value::Number(n.to_string())).collect()
+        };
+
+        assert_eq!(
+            ids("$.items[?@.status == 'failed'].id"),
+            numbers(&["1", "3"])
+        );
+        assert_eq!(
+            ids("$.items[?@.status != 'failed'].id"),
+            numbers(&["2", "4"])
+        );
+        assert_eq!(ids("$.items[?@.retry == 2].id"), numbers(&["3", "4"]));
+        assert_eq!(ids("$.items[?@.retry >= 2e0].id"), numbers(&["3", "4"]));
+        assert_eq!(ids("$.items[?!@.status].id"), numbers(&["4"]));
+        assert_eq!(
+            ids("$.items[?@.status == 'ok' || @.id > 3].id"),
+            numbers(&["2", "4"])
+        );
+        assert_eq!(
+            ids("$.items[?(@.id < 3 && @.tags[0]) || @.retry].id"),
+            numbers(&["1", "3", "4"])
+        );
+        assert_eq!(ids("$.items[?@.tags == @.missing].id"), numbers(&["4"]));
+        assert_eq!(ids("$.items[?@.id == $.items[1].id].id"), numbers(&["2"]));
+        assert_eq!(ids("$.items[?@.tags[?@ == 'c']].id"), numbers(&["3"]));
+    }
+
+    #[test]
+    fn test_jsonpath_functions() {
+        let value = parse(
+            r#"
+[
+  {"name": "alpha", "tags": ["x", "y", "z"]},
+  {"name": "beta", "tags": ["x"]},
+  {"name": "Gamma", "tags": {"a": 1, "b": 2}},
+  {"name": "日本語", "tags": "xy"}
+]
+"#,
+        );
+
+        let names = |query: &str| -> Vec<Value> {
+            value
+                .query(query)
+                .unwrap()
+                .into_iter()
+                .map(|node| node.value.clone())
+                .collect()
+        };
+        let strings = |ss: &[&str]| -> Vec<Value> {
+            ss.iter().map(|s| Value::String(s.to_string())).collect()
+        };
+
+        assert_eq!(
+            names("$[?length(@.tags) >= 2].name"),
+            strings(&["alpha", "Gamma", "日本語"])
+        );
+        assert_eq!(names("$[?length(@.name) == 3].name"), strings(&["日本語"]));
+        assert_eq!(names("$[?count(@.tags.*) == 1].name"), strings(&["beta"]));
+        assert_eq!(
+            names("$[?match(@.name, '[a-z]+')].name"),
+            strings(&["alpha", "beta"])
+        );
+        assert_eq!(names("$[?match(@.name, 'a')].name"), strings(&[]));
+        assert_eq!(
+            names("$[?search(@.name, 'a$|mm')].name"),
+            strings(&["Gamma"])
+        );
+        assert_eq!(names("$[?search(@.name, '[')].name"), strings(&[]));
+        assert_eq!(
+            names("$[?match(@.tags, @.tags)].name"),
+            strings(&["日本語"])
+        );
+        assert_eq!(names("$[?value(@.tags.a) == 1].name"), strings(&["Gamma"]));
+        assert_eq!(
+            names("$[?value(@..a) == 1 || value(@.tags[0]) == 'x'].name"),
+            strings(&["alpha", "beta", "Gamma"])
+        );
+    }
+
+    #[test]
+    fn test_jsonpath_slice() {
+        let value = parse("[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]");
+        let indices = |query: &str| -> Vec<String> {
+            value
+                .query(query)
+                .unwrap()
+                .into_iter()
+                .map(|node| match node.value {
+                    Value::Number(n) => n.clone(),
+                    _ => panic!("Should be Number"),
+                })
+                .collect()
+        };
+
+        assert_eq!(indices("$[1:3]"), vec!["1", "2"]);
+        assert_eq!(indices("$[5:]"), vec!["5", "6", "7", "8", "9"]);
+        assert_eq!(indices("$[1:5:2]"), vec!["1", "3"]);
+        assert_eq!(indices("$[5:1:-2]"), vec!["5", "3"]);
+        assert_eq!(indices("$[::-3]"), vec!["9", "6", "3", "0"]);
+        assert_eq!(indices("$[-2:]"), vec!["8", "9"]);
+        assert_eq!(indices("$[-20:2]"), vec!["0", "1"]);
+        assert_eq!(indices("$[ 1 : 2 ]"), vec!["1"]);
+        assert!(indices("$[::0]").is_empty());
+        assert!(indices("$[3:1]").is_empty());
+    }
+
+    #[test]
+    fn test_jsonpath_names() {
+        let value = parse(r#"{"a b": {"c'd": 1, "\t": 2}, "日本": [true]}"#);
+
+        assert_eq!(ordered_paths(&value, "$['a b']"), vec!["$['a b']"]);
+        assert_eq!(
+            ordered_paths(&value, r#"$["a b"]['c\'d']"#),
+            vec![r#"$['a b']['c\'d']"#]
+        );
+        assert_eq!(
+            ordered_paths(&value, r#"$['a b']["\t"]"#),
+            vec![r#"$['a b']['\t']"#]
+        );
+        assert_eq!(ordered_paths(&value, "$.日本[0]"), vec!["$['日本'][0]"]);
+        assert_eq!(
+            value.query("$['a b']['c\\'d']").unwrap()[0].pointer(),
+            "/a b/c'd"
+        );
+        assert!(ordered_paths(&value, "$.missing").is_empty());
+    }
+
+    #[test]
+    fn test_jsonpath_parse_error() {
+        let errors = [
+            ("", JsonPathError::Syntax(0)),
+            ("$.", JsonPathError::Syntax(2)),
+            ("$[01]", JsonPathError::Syntax(2)),
+            ("$[-0]", JsonPathError::Syntax(2)),
+            ("$[9007199254740992]", JsonPathError::Syntax(2)),
+            ("$['a'", JsonPathError::Syntax(5)),
+            ("$ ", JsonPathError::Syntax(1)),
+            ("$[?@.a == 1 == 2]", JsonPathError::Syntax(12)),
+            ("$[?1]", JsonPathError::Type(3)),
+            ("$[?@.* == 1]", JsonPathError::Type(3)),
+            ("$[?length(@.a)]", JsonPathError::Type(3)),
+            ("$[?count(1) == 1]", JsonPathError::Type(9)),
+            ("$[?length(@.*) == 1]", JsonPathError::Type(10)),
+            ("$[?match(@.a) == 1]", JsonPathError::Type(3)),
+            ("$[?!@.a == 1]", JsonPathError::Syntax(8)),
+            (
+                "$[?foo(@.a)]",
+                JsonPathError::UnknownFunction("foo".to_string()),
+            ),
+        ];
+
+        for (query, error) in errors.iter() {
+            assert_eq!(JsonPath::parse(query).unwrap_err(), *error, "{}", query);
+        }
+    }
+}
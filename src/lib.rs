@@ -4,7 +4,52 @@
 //   "key" : "value"
 // }
 
+mod canonical;
+mod cmp;
+mod convert;
+mod cst;
+mod cursor;
 mod de;
+mod diff;
+mod extract;
+mod json;
+mod jsonpath;
+mod macros;
+mod merge_patch;
+mod number;
+mod options;
+mod patch;
+mod pointer;
+mod raw;
+mod regex;
+mod schema;
+mod ser;
 mod token;
+mod unicode;
+mod writer;
 
-pub use de::Deserializer;
+pub use canonical::{canonical_number, to_canonical_string, CanonicalError};
+pub use cmp::Semantic;
+pub use convert::ConversionError;
+pub use cst::{Document, EditError};
+pub use cursor::Cursor;
+pub use de::{from_slice, Deserializer, Diagnostic, Value};
+pub use diff::{diff, diff_with, Change, DiffOptions};
+pub use extract::{extract, extract_with_options, ExtractError};
+pub use json::{FromJson, FromJsonError, ToJson};
+pub use jsonpath::{JsonPath, JsonPathError, Node, PathElement};
+pub use merge_patch::create_merge_patch;
+pub use options::{DuplicateKeys, ParserOptions};
+pub use patch::{apply_patch, create_patch, parse_patch, Operation, PatchError, PatchErrorKind};
+pub use pointer::PointerError;
+pub use raw::RawValue;
+pub use schema::{Schema, SchemaError, ValidationError};
+pub use ser::{
+    to_string, to_string_pretty, to_string_with, NonFiniteNumbers, SerializeError,
+    SerializerOptions,
+};
+pub use token::Error;
+pub use writer::{JsonWriter, WriterError};
+
+#[cfg(feature = "derive")]
+pub use ym_derive::{FromJson, ToJson};
@@ -0,0 +1,111 @@
+// `json!` macro to build a `Value` with JSON-like syntax
+//
+// let value = json!({
+//   "name": name,
+//   "tags": [1, 2, x],
+//   "parent": null
+// });
+//
+// Interpolated Rust expressions are converted with `Value::from`.
+
+/// Builds a `Value` from a JSON-like literal.
+///
+/// Object keys are string literals or any expression in parentheses which converts into `String`.
+#[macro_export]
+macro_rules! json {
+    (null) => {
+        $crate::Value::Null
+    };
+    ([]) => {
+        $crate::Value::Array(::std::vec::Vec::new())
+    };
+    ([ $($tt:tt)+ ]) => {
+        $crate::Value::Array($crate::json_internal!(@array [] () $($tt)+))
+    };
+    ({}) => {
+        $crate::Value::Object(::std::collections::HashMap::new())
+    };
+    ({ $($tt:tt)+ }) => {{
+        let mut object = ::std::collections::HashMap::new();
+        $crate::json_internal!(@object object $($tt)+);
+        $crate::Value::Object(object)
+    }};
+    ($other:expr) => {
+        $crate::Value::from($other)
+    };
+}
+
+/// Munches the tokens of array elements and object members for `json!`, one token at a time, as
+/// an element can be several tokens such as `-1` or `a + b`.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! json_internal {
+    // Arrays: [elements] (tokens of the current element) rest
+    (@array [$($elems:expr,)*] ($($cur:tt)+) , $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json!($($cur)+),] () $($rest)*)
+    };
+    (@array [$($elems:expr,)*] ($($cur:tt)+)) => {
+        vec![$($elems,)* $crate::json!($($cur)+)]
+    };
+    (@array [$($elems:expr,)*] ()) => {
+        vec![$($elems,)*]
+    };
+    (@array [$($elems:expr,)*] ($($cur:tt)*) $next:tt $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)*] ($($cur)* $next) $($rest)*)
+    };
+
+    // Objects: object key (tokens of the current value) rest
+    (@object $object:ident) => {};
+    (@object $object:ident $key:tt : $($rest:tt)*) => {
+        $crate::json_internal!(@member $object $key () $($rest)*);
+    };
+    (@member $object:ident $key:tt ($($cur:tt)+) , $($rest:tt)*) => {
+        $object.insert(::std::string::String::from($key), $crate::json!($($cur)+));
+        $crate::json_internal!(@object $object $($rest)*);
+    };
+    (@member $object:ident $key:tt ($($cur:tt)+)) => {
+        $object.insert(::std::string::String::from($key), $crate::json!($($cur)+));
+    };
+    (@member $object:ident $key:tt ($($cur:tt)*) $next:tt $($rest:tt)*) => {
+        $crate::json_internal!(@member $object $key ($($cur)* $next) $($rest)*);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::de::{parse, Value};
+
+    #[test]
+    fn test_json() {
+        assert_eq!(json!(null), Value::Null);
+        assert_eq!(json!(true), Value::Bool(true));
+        assert_eq!(json!(-1), Value::Number("-1".to_string()));
+        assert_eq!(json!("a"), Value::String("a".to_string()));
+        assert_eq!(json!([]), Value::Array(vec![]));
+        assert_eq!(json!({}), Value::Object(HashMap::new()));
+
+        let name = "ym".to_string();
+        let x = 3;
+        let key = "computed";
+        assert_eq!(
+            json!({
+                "name": name,
+                "tags": [1, 2, x, x * 2 - 1, [null], {}],
+                "nested": {"ok": x > 2, "none": null,},
+                (key): "value",
+            }),
+            parse(
+                r#"
+{
+  "name": "ym",
+  "tags": [1, 2, 3, 5, [null], {}],
+  "nested": {"ok": true, "none": null},
+  "computed": "value"
+}
+"#
+            )
+        );
+    }
+}
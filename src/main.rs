@@ -0,0 +1,581 @@
+// ym command-line tool
+//
+// ym check [FILE]...
+// ym fmt [--indent N | --tab] [--in-place] [FILE]...
+// ym minify [FILE]...
+// ym get [--raw] [--lines] FILE PATH
+// ym diff [--patch] [--exact-numbers] [--no-color] FILE FILE
+//
+// Files default to stdin, and "-" also means stdin. The exit status is 1 for invalid JSON, a
+// missing path or different files, and 2 for other errors such as bad arguments or unreadable
+// files.
+
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal, Read, Write};
+use std::process;
+
+use ym::{
+    Change, Deserializer, DiffOptions, Document, JsonPath, ParserOptions, PointerError,
+    SerializeError, Value,
+};
+
+/// Arrays and objects nested deeper than this are errors, so that hostile input cannot overflow
+/// the stack of the recursive parsers.
+const MAX_DEPTH: usize = 128;
+
+const USAGE: &str = "\
+usage: ym <command> [options] [FILE]...
+
+commands:
+  check             validate JSON, and report every error as FILE:LINE:COLUMN
+  fmt               pretty-print JSON
+  minify            remove all whitespaces
+  get FILE PATH     print the values at a JSON Pointer such as /items/0, or a JSONPath such
+                    as $.items[*].id
+  diff FILE FILE    print added, removed and changed values
+
+options of fmt:
+  --indent N        indent with N spaces (default: 2)
+  --tab             indent with a tab
+  -i, --in-place    rewrite the files instead of printing them
+
+options of get:
+  -r, --raw         print strings without quotes
+  -l, --lines       print each element of arrays on its own line
+
+options of diff:
+  --patch           print a JSON Patch instead
+  --exact-numbers   tell numbers of different spellings such as 1.0 and 1.00 apart
+  --no-color        do not color the output even on a terminal
+
+FILE defaults to stdin.";
+
+#[derive(Eq, PartialEq, Debug)]
+enum Command {
+    Check,
+    Fmt {
+        indent: String,
+        in_place: bool,
+    },
+    Minify,
+    Get {
+        path: String,
+        raw: bool,
+        lines: bool,
+    },
+    Diff {
+        patch: bool,
+        exact_numbers: bool,
+        color: bool,
+    },
+}
+
+#[derive(Eq, PartialEq, Debug)]
+struct Args {
+    command: Command,
+    files: Vec<String>,
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{}", USAGE);
+        return;
+    }
+
+    let args = match parse_args(&args) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("ym: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+
+    process::exit(run(&args));
+}
+
+fn parse_args(args: &[String]) -> Result<Args, String> {
+    let (command, rest) = match args.split_first() {
+        Some(split) => split,
+        None => return Err("missing command".to_string()),
+    };
+
+    let mut command = match command.as_str() {
+        "check" => Command::Check,
+        "fmt" => Command::Fmt {
+            indent: "  ".to_string(),
+            in_place: false,
+        },
+        "minify" => Command::Minify,
+        "get" => Command::Get {
+            path: String::new(),
+            raw: false,
+            lines: false,
+        },
+        "diff" => Command::Diff {
+            patch: false,
+            exact_numbers: false,
+            color: io::stdout().is_terminal(),
+        },
+        _ => return Err(format!("unknown command \"{}\"", command)),
+    };
+
+    let mut files = vec![];
+    let mut rest = rest.iter();
+    let mut options = true;
+
+    while let Some(arg) = rest.next() {
+        if !options || arg == "-" || !arg.starts_with('-') {
+            files.push(arg.clone());
+            continue;
+        }
+
+        match (&mut command, arg.as_str()) {
+            (_, "--") => options = false,
+            (Command::Fmt { indent, .. }, "--indent") => {
+                let n: usize = rest
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .ok_or("--indent needs a number of spaces")?;
+                *indent = " ".repeat(n);
+            }
+            (Command::Fmt { indent, .. }, "--tab") => *indent = "\t".to_string(),
+            (Command::Fmt { in_place, .. }, "-i")
+            | (Command::Fmt { in_place, .. }, "--in-place") => *in_place = true,
+            (Command::Get { raw, .. }, "-r") | (Command::Get { raw, .. }, "--raw") => *raw = true,
+            (Command::Get { lines, .. }, "-l") | (Command::Get { lines, .. }, "--lines") => {
+                *lines = true
+            }
+            (Command::Diff { patch, .. }, "--patch") => *patch = true,
+            (Command::Diff { exact_numbers, .. }, "--exact-numbers") => *exact_numbers = true,
+            (Command::Diff { color, .. }, "--no-color") => *color = false,
+            _ => return Err(format!("unknown option \"{}\"", arg)),
+        }
+    }
+
+    if let Command::Get { path, .. } = &mut command {
+        if files.len() != 2 {
+            return Err("get needs FILE and PATH".to_string());
+        }
+        *path = files.pop().unwrap();
+    }
+    if let (Command::Diff { .. }, false) = (&command, files.len() == 2) {
+        return Err("diff needs two files".to_string());
+    }
+
+    Ok(Args { command, files })
+}
+
+/// Runs the command on every file, and returns the exit status.
+fn run(args: &Args) -> i32 {
+    if let Command::Diff { .. } = args.command {
+        return run_diff(args);
+    }
+
+    let stdin = vec!["-".to_string()];
+    let files = if args.files.is_empty() {
+        &stdin
+    } else {
+        &args.files
+    };
+
+    let mut status = 0;
+
+    for file in files {
+        let name = if file == "-" { "<stdin>" } else { file };
+
+        let input = match read(file) {
+            Ok(input) => input,
+            Err(e) => {
+                eprintln!("ym: {}: {}", name, e);
+                status = 2;
+                continue;
+            }
+        };
+
+        let document = match parse(&input) {
+            Ok(document) => document,
+            Err(messages) => {
+                for message in messages {
+                    eprintln!("{}:{}", name, message);
+                }
+                status = status.max(1);
+                continue;
+            }
+        };
+
+        let output = match &args.command {
+            Command::Check => continue,
+            Command::Fmt { indent, .. } => document.pretty(indent) + "\n",
+            Command::Minify => document.minify() + "\n",
+            Command::Get { path, raw, lines } => {
+                let value = document.to_value();
+                match select(&value, path) {
+                    Ok(values) if values.is_empty() => {
+                        eprintln!("ym: {}: no value at {}", name, path);
+                        status = status.max(1);
+                        continue;
+                    }
+                    Ok(values) => match render(&values, *raw, *lines) {
+                        Ok(output) => output,
+                        Err(e) => {
+                            eprintln!("ym: {}: {}", name, e);
+                            status = 2;
+                            continue;
+                        }
+                    },
+                    Err(message) => {
+                        eprintln!("ym: {}", message);
+                        status = 2;
+                        continue;
+                    }
+                }
+            }
+            Command::Diff { .. } => unreachable!("diff reads two files at once"),
+        };
+
+        let result = match &args.command {
+            Command::Fmt { in_place: true, .. } if file != "-" => {
+                if output == input {
+                    Ok(())
+                } else {
+                    fs::write(file, output)
+                }
+            }
+            _ => io::stdout().write_all(output.as_bytes()),
+        };
+
+        if let Err(e) = result {
+            eprintln!("ym: {}: {}", name, e);
+            status = 2;
+        }
+    }
+
+    status
+}
+
+fn run_diff(args: &Args) -> i32 {
+    let (patch, exact_numbers, color) = match args.command {
+        Command::Diff {
+            patch,
+            exact_numbers,
+            color,
+        } => (patch, exact_numbers, color),
+        _ => unreachable!(),
+    };
+
+    let mut values = vec![];
+    for file in args.files.iter() {
+        let name = if file == "-" { "<stdin>" } else { file };
+        match read(file) {
+            Ok(input) => match parse(&input) {
+                Ok(document) => values.push(document.to_value()),
+                Err(messages) => {
+                    for message in messages {
+                        eprintln!("{}:{}", name, message);
+                    }
+                    return 1;
+                }
+            },
+            Err(e) => {
+                eprintln!("ym: {}: {}", name, e);
+                return 2;
+            }
+        }
+    }
+
+    let changes = ym::diff_with(&values[0], &values[1], &DiffOptions { exact_numbers });
+    let output = if patch {
+        let operations = changes
+            .iter()
+            .map(|c| c.to_operation().to_value())
+            .collect();
+        match ym::to_string_pretty(&Value::Array(operations), "  ") {
+            Ok(patch) => patch + "\n",
+            Err(e) => {
+                eprintln!("ym: {}", e);
+                return 2;
+            }
+        }
+    } else {
+        unified(&changes, color)
+    };
+
+    if let Err(e) = io::stdout().write_all(output.as_bytes()) {
+        eprintln!("ym: {}", e);
+        return 2;
+    }
+
+    if changes.is_empty() {
+        0
+    } else {
+        1
+    }
+}
+
+/// Lines of the changes, with removed lines in red and added ones in green if `color`.
+fn unified(changes: &[Change], color: bool) -> String {
+    let mut out = String::new();
+
+    for change in changes {
+        for line in change.to_string().lines() {
+            match (color, line.starts_with('-')) {
+                (false, _) => out.push_str(line),
+                (true, true) => out.push_str(&format!("\x1b[31m{}\x1b[0m", line)),
+                (true, false) => out.push_str(&format!("\x1b[32m{}\x1b[0m", line)),
+            }
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+fn read(file: &str) -> io::Result<String> {
+    if file == "-" {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        Ok(input)
+    } else {
+        fs::read_to_string(file)
+    }
+}
+
+/// Parses into a `Document` which keeps the order of members. Errors are "LINE:COLUMN: message",
+/// all of them rather than only the first one as `Deserializer` recovers, each followed by a
+/// line of a hint if any.
+fn parse(input: &str) -> Result<Document, Vec<String>> {
+    let options = ParserOptions::new().max_depth(MAX_DEPTH);
+    let (_, diagnostics) = Deserializer::new_with_options(input, options).parse_recovering();
+    if !diagnostics.is_empty() {
+        return Err(diagnostics
+            .iter()
+            .map(|d| match d.error.hint() {
+                Some(hint) => format!("{}\n  hint: {}", d, hint),
+                None => d.to_string(),
+            })
+            .collect());
+    }
+
+    Document::parse_located(input, MAX_DEPTH)
+        .map_err(|(e, (line, column))| vec![format!("{}:{}: {}", line, column, e)])
+}
+
+/// Values at a JSONPath if `path` starts with "$", or otherwise at a JSON Pointer. A missing
+/// value is not an error but nothing.
+fn select<'a>(value: &'a Value, path: &str) -> Result<Vec<&'a Value>, String> {
+    if path.starts_with('$') {
+        let path = JsonPath::parse(path).map_err(|e| format!("{}: {}", path, e))?;
+        return Ok(path
+            .query(value)
+            .into_iter()
+            .map(|node| node.value)
+            .collect());
+    }
+
+    match value.pointer(path) {
+        Ok(value) => Ok(vec![value]),
+        Err(PointerError::Malformed) => Err(format!("{}: {}", path, PointerError::Malformed)),
+        Err(_) => Ok(vec![]),
+    }
+}
+
+/// A line for each value, or for each element of arrays with `lines`. Values on their own are
+/// pretty-printed, and elements are compact to fit on a line.
+fn render(values: &[&Value], raw: bool, lines: bool) -> Result<String, SerializeError> {
+    let line = |value: &Value, pretty: bool| match value {
+        Value::String(s) if raw => Ok(s.clone()),
+        _ if pretty => ym::to_string_pretty(value, "  "),
+        _ => ym::to_string(value),
+    };
+
+    let mut out = String::new();
+    for value in values {
+        match value {
+            Value::Array(array) if lines => {
+                for element in array {
+                    out.push_str(&line(element, false)?);
+                    out.push('\n');
+                }
+            }
+            _ => {
+                out.push_str(&line(value, true)?);
+                out.push('\n');
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Result<Args, String> {
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        parse_args(&args)
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            args(&["check", "a.json", "-", "b.json"]),
+            Ok(Args {
+                command: Command::Check,
+                files: vec!["a.json".to_string(), "-".to_string(), "b.json".to_string()],
+            })
+        );
+        assert_eq!(
+            args(&["fmt", "--indent", "4", "-i", "--", "--tab"]),
+            Ok(Args {
+                command: Command::Fmt {
+                    indent: "    ".to_string(),
+                    in_place: true
+                },
+                files: vec!["--tab".to_string()],
+            })
+        );
+        assert_eq!(
+            args(&["fmt", "--tab"]).unwrap().command,
+            Command::Fmt {
+                indent: "\t".to_string(),
+                in_place: false
+            }
+        );
+
+        assert!(args(&[]).is_err());
+        assert!(args(&["lint"]).is_err());
+        assert!(args(&["fmt", "--indent"]).is_err());
+        assert!(args(&["fmt", "--indent", "x"]).is_err());
+        assert!(args(&["minify", "--tab"]).is_err());
+
+        assert_eq!(
+            args(&["get", "-r", "a.json", "--lines", "/a"]),
+            Ok(Args {
+                command: Command::Get {
+                    path: "/a".to_string(),
+                    raw: true,
+                    lines: true
+                },
+                files: vec!["a.json".to_string()],
+            })
+        );
+        assert!(args(&["get", "a.json"]).is_err());
+        assert!(args(&["get", "-", "/a", "/b"]).is_err());
+
+        assert_eq!(
+            args(&["diff", "--patch", "--no-color", "a.json", "b.json"]),
+            Ok(Args {
+                command: Command::Diff {
+                    patch: true,
+                    exact_numbers: false,
+                    color: false
+                },
+                files: vec!["a.json".to_string(), "b.json".to_string()],
+            })
+        );
+        assert!(args(&["diff", "a.json"]).is_err());
+    }
+
+    #[test]
+    fn test_unified() {
+        let changes = ym::diff(
+            &ym::json!({"a": 1, "b": [true]}),
+            &ym::json!({"a": 2, "c": null, "b": [true]}),
+        );
+
+        assert_eq!(unified(&changes, false), "- /a: 1\n+ /a: 2\n+ /c: null\n");
+        assert_eq!(unified(&changes[1..], true), "\x1b[32m+ /c: null\x1b[0m\n");
+    }
+
+    #[test]
+    fn test_select() {
+        let value = ym::Deserializer::new(r#"{"a": [{"id": "x"}, {"id": 2}], "b": null}"#)
+            .parse()
+            .unwrap()
+            .unwrap();
+        let get = |path: &str, raw: bool, lines: bool| {
+            select(&value, path).map(|values| render(&values, raw, lines).unwrap())
+        };
+
+        assert_eq!(get("/a/0/id", false, false), Ok("\"x\"\n".to_string()));
+        assert_eq!(get("/a/0/id", true, false), Ok("x\n".to_string()));
+        assert_eq!(get("/b", true, false), Ok("null\n".to_string()));
+        assert_eq!(
+            get("/a/0", false, false),
+            Ok("{\n  \"id\": \"x\"\n}\n".to_string())
+        );
+        assert_eq!(
+            get("/a", true, true),
+            Ok("{\"id\":\"x\"}\n{\"id\":2}\n".to_string())
+        );
+        assert_eq!(get("$.a[*].id", true, false), Ok("x\n2\n".to_string()));
+        assert_eq!(get("$..id", false, true), Ok("\"x\"\n2\n".to_string()));
+
+        assert_eq!(get("/c", false, false), Ok("".to_string()));
+        assert_eq!(get("/a/5", false, false), Ok("".to_string()));
+        assert_eq!(get("$.c", false, false), Ok("".to_string()));
+        assert!(get("a", false, false).is_err());
+        assert!(get("$[", false, false).is_err());
+    }
+
+    #[test]
+    fn test_run_diff_status() {
+        let dir = env::temp_dir();
+        let file = |name: &str, content: &str| {
+            let path = dir.join(format!("ym-test-{}-{}", process::id(), name));
+            fs::write(&path, content).unwrap();
+            path.to_string_lossy().into_owned()
+        };
+        let a = file("a.json", r#"{"a": 1}"#);
+        let b = file("b.json", r#"{"a": 2}"#);
+        let invalid = file("invalid.json", r#"{"a": }"#);
+        let missing = dir.join("ym-test-missing.json");
+        let diff = |files: &[&str]| {
+            let mut arguments = vec!["diff", "--no-color"];
+            arguments.extend_from_slice(files);
+            run(&args(&arguments).unwrap())
+        };
+
+        assert_eq!(diff(&[&a, &a]), 0);
+        assert_eq!(diff(&[&a, &b]), 1);
+        assert_eq!(diff(&[&a, &invalid]), 1);
+        assert_eq!(diff(&[&a, &missing.to_string_lossy()]), 2);
+
+        for path in [a, b, invalid].iter() {
+            fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("{\"b\": 1, \"a\": [true]}").unwrap().minify(),
+            "{\"b\":1,\"a\":[true]}"
+        );
+        assert_eq!(
+            parse("{\n  \"a\": nul\n}").unwrap_err(),
+            vec!["2:8: unexpected token at `$.a`"]
+        );
+        assert_eq!(
+            parse("{\n  \"a\": nul,\n  \"b\" 1,\n  \"c\": [1 2]\n}").unwrap_err(),
+            vec![
+                "2:8: unexpected token at `$.a`",
+                "3:7: expected ':' after key \"b\"\n  hint: write the member as \"b\": value",
+                "4:11: missing ',' between elements at `$.c`\n  hint: add ',' before this"
+            ]
+        );
+        assert_eq!(
+            parse("[1,").unwrap_err(),
+            vec!["1:4: unexpected end of input in array opened at line 1 at `$[1]`\n  hint: add the closing ']'"]
+        );
+
+        let nested = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert!(parse(&nested).is_ok());
+        let deeper = "[".repeat(20000);
+        assert!(parse(&deeper).unwrap_err()[0].starts_with("1:129: nesting is too deep"));
+    }
+}
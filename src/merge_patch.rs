@@ -0,0 +1,140 @@
+/// JSON Merge Patch (RFC 7396)
+///
+/// {
+///   "title": "Hello!",
+///   "author": { "familyName": null },
+///   "tags": [ "example" ]
+/// }
+use std::collections::HashMap;
+
+use crate::de::Value;
+
+impl Value {
+    /// Applies a merge patch. `null` removes a member, an object is merged recursively, and any
+    /// other value replaces the target.
+    pub fn merge_patch(&mut self, patch: &Value) {
+        let patch = match patch {
+            Value::Object(patch) => patch,
+            _ => {
+                *self = patch.clone();
+                return;
+            }
+        };
+
+        if !matches!(self, Value::Object(_)) {
+            *self = Value::Object(HashMap::new());
+        }
+
+        if let Value::Object(object) = self {
+            for (key, value) in patch.iter() {
+                match value {
+                    Value::Null => {
+                        object.remove(key);
+                    }
+                    _ => object
+                        .entry(key.clone())
+                        .or_insert(Value::Null)
+                        .merge_patch(value),
+                }
+            }
+        }
+    }
+}
+
+/// A merge patch which turns `from` into `to`.
+///
+/// Merge patches can not set a member to `null`, so such members of `to` are removed instead.
+pub fn create_merge_patch(from: &Value, to: &Value) -> Value {
+    let (from, to) = match (from, to) {
+        (Value::Object(from), Value::Object(to)) => (from, to),
+        _ => return to.clone(),
+    };
+
+    let mut patch = HashMap::new();
+
+    for key in from.keys() {
+        if !to.contains_key(key) {
+            patch.insert(key.clone(), Value::Null);
+        }
+    }
+
+    for (key, value) in to.iter() {
+        match from.get(key) {
+            Some(old) if old == value => {}
+            Some(old) => {
+                patch.insert(key.clone(), create_merge_patch(old, value));
+            }
+            None => {
+                patch.insert(key.clone(), value.clone());
+            }
+        }
+    }
+
+    Value::Object(patch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::de::parse;
+
+    #[test]
+    fn test_value_merge_patch() {
+        // RFC 7396 appendix A
+        let cases = [
+            (r#"{"a":"b"}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"b":"c"}"#, r#"{"a":"b","b":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"a":null}"#, r#"{}"#),
+            (r#"{"a":"b","b":"c"}"#, r#"{"a":null}"#, r#"{"b":"c"}"#),
+            (r#"{"a":["b"]}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"c"}"#, r#"{"a":["b"]}"#, r#"{"a":["b"]}"#),
+            (
+                r#"{"a":{"b":"c"}}"#,
+                r#"{"a":{"b":"d","c":null}}"#,
+                r#"{"a":{"b":"d"}}"#,
+            ),
+            (r#"{"a":[{"b":"c"}]}"#, r#"{"a":[1]}"#, r#"{"a":[1]}"#),
+            (r#"["a","b"]"#, r#"["c","d"]"#, r#"["c","d"]"#),
+            (r#"{"a":"b"}"#, r#"["c"]"#, r#"["c"]"#),
+            (r#"{"a":"foo"}"#, "null", "null"),
+            (r#"{"a":"foo"}"#, r#""bar""#, r#""bar""#),
+            (r#"{"e":null}"#, r#"{"a":1}"#, r#"{"e":null,"a":1}"#),
+            (r#"[1,2]"#, r#"{"a":"b","c":null}"#, r#"{"a":"b"}"#),
+            (
+                r#"{}"#,
+                r#"{"a":{"bb":{"ccc":null}}}"#,
+                r#"{"a":{"bb":{}}}"#,
+            ),
+        ];
+
+        for (target, patch, expected) in cases.iter() {
+            let mut value = parse(target);
+            value.merge_patch(&parse(patch));
+            assert_eq!(value, parse(expected), "{} {}", target, patch);
+        }
+    }
+
+    #[test]
+    fn test_create_merge_patch() {
+        let cases = [
+            (r#"{"a": 1}"#, r#"{"a": 1}"#, "{}"),
+            (
+                r#"{"a": 1, "b": {"c": 2, "d": [1]}, "e": {"f": 1}}"#,
+                r#"{"b": {"c": 2, "d": [2]}, "e": 3, "g": {"h": true}}"#,
+                r#"{"a": null, "b": {"d": [2]}, "e": 3, "g": {"h": true}}"#,
+            ),
+            (r#"{"a": 1}"#, "[1]", "[1]"),
+            ("[1]", r#"{"a": 1}"#, r#"{"a": 1}"#),
+        ];
+
+        for (from, to, expected) in cases.iter() {
+            let (from, to) = (parse(from), parse(to));
+            let patch = create_merge_patch(&from, &to);
+            assert_eq!(patch, parse(expected));
+
+            let mut value = from.clone();
+            value.merge_patch(&patch);
+            assert_eq!(value, to);
+        }
+    }
+}
@@ -0,0 +1,361 @@
+/// Exact decimal view of a number lexeme
+///
+/// - "100"     -> +1 * 10^2
+/// - "-0.0010" -> -1 * 10^-3
+/// - "1.5e3"   -> +15 * 10^2
+use std::cmp::Ordering;
+
+/// `(-1)^negative * digits * 10^exponent`. Leading and trailing zeros are stripped from
+/// `digits`, and zero has no digits and is never negative.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub(crate) struct Decimal {
+    negative: bool,
+    digits: Vec<u8>,
+    exponent: i64,
+}
+
+impl Decimal {
+    /// Parses `-? int frac? exp?` as in JSON, or returns `None` for anything else.
+    pub(crate) fn parse(lexeme: &str) -> Option<Decimal> {
+        let mut bytes = lexeme.as_bytes();
+
+        let negative = bytes.first() == Some(&b'-');
+        if negative {
+            bytes = &bytes[1..];
+        }
+
+        let int_len = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+        if int_len == 0 || (int_len > 1 && bytes[0] == b'0') {
+            return None;
+        }
+        let (int, mut rest) = bytes.split_at(int_len);
+
+        let mut frac: &[u8] = &[];
+        if rest.first() == Some(&b'.') {
+            let frac_len = rest[1..].iter().take_while(|b| b.is_ascii_digit()).count();
+            if frac_len == 0 {
+                return None;
+            }
+            frac = &rest[1..=frac_len];
+            rest = &rest[frac_len + 1..];
+        }
+
+        let mut exponent: i64 = 0;
+        if let Some(b'e') | Some(b'E') = rest.first() {
+            rest = &rest[1..];
+
+            let exp_negative = rest.first() == Some(&b'-');
+            if let Some(b'-') | Some(b'+') = rest.first() {
+                rest = &rest[1..];
+            }
+            if rest.is_empty() || !rest.iter().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+
+            // Saturate instead of overflowing. Such numbers are far beyond any digits anyway.
+            for b in rest {
+                exponent = exponent
+                    .saturating_mul(10)
+                    .saturating_add(i64::from(b - b'0'))
+                    .min(i64::MAX / 4);
+            }
+            if exp_negative {
+                exponent = -exponent;
+            }
+            rest = &[];
+        }
+
+        if !rest.is_empty() {
+            return None;
+        }
+
+        let mut digits: Vec<u8> = int.iter().chain(frac).map(|b| b - b'0').collect();
+        exponent -= frac.len() as i64;
+
+        let leading = digits.iter().take_while(|d| **d == 0).count();
+        digits.drain(..leading);
+        while digits.last() == Some(&0) {
+            digits.pop();
+            exponent += 1;
+        }
+
+        if digits.is_empty() {
+            return Some(Decimal::zero());
+        }
+
+        Some(Decimal {
+            negative,
+            digits,
+            exponent,
+        })
+    }
+
+    pub(crate) fn zero() -> Decimal {
+        Decimal {
+            negative: false,
+            digits: vec![],
+            exponent: 0,
+        }
+    }
+
+    pub(crate) fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    pub(crate) fn is_integer(&self) -> bool {
+        self.exponent >= 0
+    }
+
+    /// Plain decimal digits of an integer such as "-1200", or `None` for a fraction or an
+    /// integer of more than `max_digits` digits.
+    pub(crate) fn to_integer_string(&self, max_digits: usize) -> Option<String> {
+        if self.is_zero() {
+            return Some("0".to_string());
+        }
+        if !self.is_integer() || self.magnitude() > max_digits as i64 {
+            return None;
+        }
+
+        let mut s = String::with_capacity(self.magnitude() as usize + 1);
+        if self.negative {
+            s.push('-');
+        }
+        s.extend(self.digits.iter().map(|d| char::from(b'0' + d)));
+        s.extend(std::iter::repeat_n('0', self.exponent as usize));
+
+        Some(s)
+    }
+
+    /// Whether `self / divisor` is an integer, computed exactly.
+    pub(crate) fn is_multiple_of(&self, divisor: &Decimal) -> bool {
+        if divisor.is_zero() {
+            return false;
+        }
+        if self.is_zero() {
+            return true;
+        }
+
+        // self / divisor = (digits / divisor.digits) * 10^shift. As `digits` has no trailing
+        // zeros, a negative shift never gives an integer.
+        let shift = self.exponent - divisor.exponent;
+        if shift < 0 {
+            return false;
+        }
+
+        // Powers of 10 only cancel the factors 2 and 5 of the divisor, and there are fewer of
+        // them than 4 times its number of digits.
+        let zeros = shift.min(4 * divisor.digits.len() as i64 + 4) as usize;
+
+        let mut remainder: Vec<u8> = vec![];
+        for d in self.digits.iter().chain(std::iter::repeat_n(&0, zeros)) {
+            remainder.push(*d);
+            let leading = remainder.iter().take_while(|d| **d == 0).count();
+            remainder.drain(..leading);
+
+            while cmp_digits(&remainder, &divisor.digits) != Ordering::Less {
+                sub_digits(&mut remainder, &divisor.digits);
+            }
+        }
+
+        remainder.is_empty()
+    }
+
+    /// Exponent of the most significant digit plus one.
+    fn magnitude(&self) -> i64 {
+        self.exponent + self.digits.len() as i64
+    }
+
+    fn cmp_abs(&self, other: &Decimal) -> Ordering {
+        match (self.is_zero(), other.is_zero()) {
+            (true, true) => return Ordering::Equal,
+            (true, false) => return Ordering::Less,
+            (false, true) => return Ordering::Greater,
+            _ => {}
+        }
+
+        self.magnitude()
+            .cmp(&other.magnitude())
+            .then_with(|| self.digits.cmp(&other.digits))
+    }
+}
+
+/// The value of the lexemes `NaN`, `Infinity` and `-Infinity`, which are allowed by
+/// `ParserOptions::non_finite_numbers` but are not JSON.
+pub(crate) fn non_finite(lexeme: &str) -> Option<f64> {
+    match lexeme {
+        "NaN" => Some(f64::NAN),
+        "Infinity" => Some(f64::INFINITY),
+        "-Infinity" => Some(f64::NEG_INFINITY),
+        _ => None,
+    }
+}
+
+/// The lexeme of a float which is NaN or infinite, as `non_finite` reads it.
+pub(crate) fn non_finite_lexeme(f: f64) -> &'static str {
+    if f.is_nan() {
+        "NaN"
+    } else if f > 0.0 {
+        "Infinity"
+    } else {
+        "-Infinity"
+    }
+}
+
+/// Compares unsigned integers of decimal digits without leading zeros.
+fn cmp_digits(a: &[u8], b: &[u8]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+/// `a -= b` for unsigned integers of decimal digits where `a >= b`. Leading zeros are stripped.
+fn sub_digits(a: &mut Vec<u8>, b: &[u8]) {
+    let mut borrow = 0;
+
+    for i in 0..a.len() {
+        let ai = a.len() - 1 - i;
+        let bd = if i < b.len() { b[b.len() - 1 - i] } else { 0 };
+        let mut d = a[ai] as i8 - bd as i8 - borrow;
+
+        borrow = 0;
+        if d < 0 {
+            d += 10;
+            borrow = 1;
+        }
+        a[ai] = d as u8;
+    }
+
+    let leading = a.iter().take_while(|d| **d == 0).count();
+    a.drain(..leading);
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Decimal) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => self.cmp_abs(other),
+            (true, true) => other.cmp_abs(self),
+        }
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Decimal) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decimal(lexeme: &str) -> Decimal {
+        Decimal::parse(lexeme).unwrap()
+    }
+
+    #[test]
+    fn test_decimal_parse() {
+        assert_eq!(
+            decimal("100"),
+            Decimal {
+                negative: false,
+                digits: vec![1],
+                exponent: 2
+            }
+        );
+        assert_eq!(
+            decimal("-0.0010"),
+            Decimal {
+                negative: true,
+                digits: vec![1],
+                exponent: -3
+            }
+        );
+        assert_eq!(
+            decimal("1.5E+3"),
+            Decimal {
+                negative: false,
+                digits: vec![1, 5],
+                exponent: 2
+            }
+        );
+        assert_eq!(decimal("-0.0e10"), Decimal::zero());
+
+        for lexeme in ["", "-", "01", "1.", ".1", "1e", "1e+", "+1", "1x", "NaN"].iter() {
+            assert_eq!(Decimal::parse(lexeme), None);
+        }
+    }
+
+    #[test]
+    fn test_decimal_cmp() {
+        assert_eq!(decimal("1"), decimal("1.0"));
+        assert_eq!(decimal("1"), decimal("1e0"));
+        assert_eq!(decimal("0"), decimal("-0"));
+        assert_eq!(decimal("100"), decimal("1E2"));
+
+        let ordered = [
+            "-1e400",
+            "-10",
+            "-9.99",
+            "-1",
+            "-0.5",
+            "0",
+            "1e-400",
+            "0.5",
+            "1",
+            "1.0000001",
+            "9",
+            "10",
+            "1e400",
+        ];
+        for pair in ordered.windows(2) {
+            assert!(decimal(pair[0]) < decimal(pair[1]), "{:?}", pair);
+        }
+    }
+
+    #[test]
+    fn test_decimal_is_multiple_of() {
+        let cases = [
+            ("10", "5", true),
+            ("10", "3", false),
+            ("0", "7", true),
+            ("4.5", "1.5", true),
+            ("4.6", "1.5", false),
+            ("0.3", "0.1", true),
+            ("1", "0.01", true),
+            ("0.01", "1", false),
+            ("1e400", "0.0001", true),
+            ("1e400", "3", false),
+            ("1e400", "1024", true),
+            ("123456789012345678901234567890", "1234567890", true),
+            ("123456789012345678901234567891", "1234567890", false),
+            ("-12", "4", true),
+            ("12", "-4", true),
+        ];
+
+        for (n, divisor, expected) in cases.iter() {
+            assert_eq!(
+                decimal(n).is_multiple_of(&decimal(divisor)),
+                *expected,
+                "{} {}",
+                n,
+                divisor
+            );
+        }
+        assert!(!decimal("1").is_multiple_of(&Decimal::zero()));
+    }
+
+    #[test]
+    fn test_decimal_to_integer_string() {
+        assert_eq!(
+            decimal("-12e2").to_integer_string(4),
+            Some("-1200".to_string())
+        );
+        assert_eq!(
+            decimal("1.50E1").to_integer_string(4),
+            Some("15".to_string())
+        );
+        assert_eq!(decimal("-0.0").to_integer_string(4), Some("0".to_string()));
+        assert_eq!(decimal("1e4").to_integer_string(4), None);
+        assert_eq!(decimal("1.5").to_integer_string(4), None);
+    }
+}
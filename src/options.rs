@@ -0,0 +1,154 @@
+/// Which of the members with the same key an object keeps.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum DuplicateKeys {
+    First,
+    Last,
+    /// Fails with `Error::DuplicateKey`. Only values being built are checked, so skipped ones are
+    /// not.
+    Error,
+}
+
+/// Options of `Deserializer`
+///
+/// let options = ParserOptions::new()
+///     .comments(true)
+///     .trailing_commas(true)
+///     .duplicate_keys(DuplicateKeys::Error)
+///     .max_depth(64);
+///
+/// The default is RFC 8259 JSON, except that the dialect is lenient about unescaped control
+/// characters and lone surrogates in strings unless `strict`.
+///
+/// Limits guard against hostile inputs such as a 1 GB string or an array of a billion `0`s. Each
+/// of them fails with its own `Error`, and all of them are unlimited by default. Lengths are in
+/// bytes. Strings are measured after unescaping, except that those only skipped over by `Cursor`,
+/// `extract` and `RawValue` are measured as they are in the input. Arrays and objects are parsed
+/// recursively, so without `max_depth` deeply nested input such as a million `[`s overflows the
+/// stack. Set it for untrusted input.
+#[derive(Clone, Debug)]
+pub struct ParserOptions {
+    pub(crate) strict: bool,
+    pub(crate) duplicate_keys: DuplicateKeys,
+    pub(crate) comments: bool,
+    pub(crate) trailing_commas: bool,
+    pub(crate) non_finite_numbers: bool,
+    pub(crate) scalar_root: bool,
+    pub(crate) lossy_utf8: bool,
+    pub(crate) max_input_length: Option<usize>,
+    pub(crate) max_string_length: Option<usize>,
+    pub(crate) max_number_length: Option<usize>,
+    pub(crate) max_members: Option<usize>,
+    pub(crate) max_nodes: Option<usize>,
+    pub(crate) max_depth: Option<usize>,
+}
+
+impl Default for ParserOptions {
+    fn default() -> ParserOptions {
+        ParserOptions {
+            strict: false,
+            duplicate_keys: DuplicateKeys::Last,
+            comments: false,
+            trailing_commas: false,
+            non_finite_numbers: false,
+            scalar_root: true,
+            lossy_utf8: false,
+            max_input_length: None,
+            max_string_length: None,
+            max_number_length: None,
+            max_members: None,
+            max_nodes: None,
+            max_depth: None,
+        }
+    }
+}
+
+impl ParserOptions {
+    pub fn new() -> ParserOptions {
+        ParserOptions::default()
+    }
+
+    /// Rejects unescaped control characters and lone surrogates in strings as RFC 8259 does.
+    pub fn strict(mut self, strict: bool) -> ParserOptions {
+        self.strict = strict;
+        self
+    }
+
+    /// `DuplicateKeys::Last` by default.
+    pub fn duplicate_keys(mut self, policy: DuplicateKeys) -> ParserOptions {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    /// Allows `//` and `/* */` comments wherever whitespaces are allowed (JSONC).
+    pub fn comments(mut self, comments: bool) -> ParserOptions {
+        self.comments = comments;
+        self
+    }
+
+    /// Allows a comma after the last element or member, such as `[1, 2,]`.
+    pub fn trailing_commas(mut self, trailing_commas: bool) -> ParserOptions {
+        self.trailing_commas = trailing_commas;
+        self
+    }
+
+    /// Allows `NaN`, `Infinity` and `-Infinity`, which become numbers of those lexemes.
+    pub fn non_finite_numbers(mut self, non_finite_numbers: bool) -> ParserOptions {
+        self.non_finite_numbers = non_finite_numbers;
+        self
+    }
+
+    /// Whether the root may be a string, number, boolean or null, which it may by default as in
+    /// RFC 8259. Otherwise it must be an array or object as in RFC 4627.
+    pub fn scalar_root(mut self, scalar_root: bool) -> ParserOptions {
+        self.scalar_root = scalar_root;
+        self
+    }
+
+    /// Replaces invalid UTF-8 sequences with U+FFFD in `from_slice`, instead of failing with
+    /// `Error::InvalidUtf8`.
+    pub fn lossy_utf8(mut self, lossy_utf8: bool) -> ParserOptions {
+        self.lossy_utf8 = lossy_utf8;
+        self
+    }
+
+    /// Length of the whole input.
+    pub fn max_input_length(mut self, limit: usize) -> ParserOptions {
+        self.max_input_length = Some(limit);
+        self
+    }
+
+    /// Length of each string, including keys.
+    pub fn max_string_length(mut self, limit: usize) -> ParserOptions {
+        self.max_string_length = Some(limit);
+        self
+    }
+
+    /// Length of each number lexeme.
+    pub fn max_number_length(mut self, limit: usize) -> ParserOptions {
+        self.max_number_length = Some(limit);
+        self
+    }
+
+    /// Elements of each array, or members of each object.
+    pub fn max_members(mut self, limit: usize) -> ParserOptions {
+        self.max_members = Some(limit);
+        self
+    }
+
+    /// Values in the whole input, including arrays and objects themselves.
+    pub fn max_nodes(mut self, limit: usize) -> ParserOptions {
+        self.max_nodes = Some(limit);
+        self
+    }
+
+    /// Nesting of arrays and objects, where 0 allows only scalars.
+    pub fn max_depth(mut self, limit: usize) -> ParserOptions {
+        self.max_depth = Some(limit);
+        self
+    }
+}
+
+/// Whether `n` is beyond `limit`.
+pub(crate) fn exceeds(limit: Option<usize>, n: usize) -> bool {
+    limit.is_some_and(|limit| n > limit)
+}
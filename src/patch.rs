@@ -0,0 +1,560 @@
+/// JSON Patch (RFC 6902)
+///
+/// [
+///   { "op": "test", "path": "/a/b/c", "value": "foo" },
+///   { "op": "remove", "path": "/a/b/c" },
+///   { "op": "add", "path": "/a/b/c", "value": [ "foo", "bar" ] },
+///   { "op": "replace", "path": "/a/b/c", "value": 42 },
+///   { "op": "move", "from": "/a/b/c", "path": "/a/b/d" },
+///   { "op": "copy", "from": "/a/b/d", "path": "/a/b/e" }
+/// ]
+use std::collections::HashMap;
+
+use crate::cmp;
+use crate::de::Value;
+use crate::pointer::{self, PointerError};
+
+/// The most cells of the LCS table of `diff_array`, which takes 8 MB.
+const MAX_LCS_TABLE: usize = 1 << 20;
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Operation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: Value },
+}
+
+/// Why the operation at `index` failed.
+#[derive(Eq, PartialEq, Debug)]
+pub struct PatchError {
+    pub index: usize,
+    pub kind: PatchErrorKind,
+}
+
+#[derive(Eq, PartialEq, Debug)]
+pub enum PatchErrorKind {
+    Pointer(PointerError),
+    TestFailed,
+    /// "from" of a move is a proper prefix of "path".
+    MoveIntoChild,
+    /// Not an operation object. Names the missing or invalid member.
+    Malformed(String),
+}
+
+impl Operation {
+    pub fn from_value(value: &Value) -> Result<Operation, PatchErrorKind> {
+        let object = match value {
+            Value::Object(object) => object,
+            _ => return Err(PatchErrorKind::Malformed("op".to_string())),
+        };
+
+        let member = |name: &str| -> Result<&Value, PatchErrorKind> {
+            object
+                .get(name)
+                .ok_or_else(|| PatchErrorKind::Malformed(name.to_string()))
+        };
+        let string = |name: &str| -> Result<String, PatchErrorKind> {
+            match member(name)? {
+                Value::String(s) => Ok(s.clone()),
+                _ => Err(PatchErrorKind::Malformed(name.to_string())),
+            }
+        };
+
+        match string("op")?.as_str() {
+            "add" => Ok(Operation::Add {
+                path: string("path")?,
+                value: member("value")?.clone(),
+            }),
+            "remove" => Ok(Operation::Remove {
+                path: string("path")?,
+            }),
+            "replace" => Ok(Operation::Replace {
+                path: string("path")?,
+                value: member("value")?.clone(),
+            }),
+            "move" => Ok(Operation::Move {
+                from: string("from")?,
+                path: string("path")?,
+            }),
+            "copy" => Ok(Operation::Copy {
+                from: string("from")?,
+                path: string("path")?,
+            }),
+            "test" => Ok(Operation::Test {
+                path: string("path")?,
+                value: member("value")?.clone(),
+            }),
+            _ => Err(PatchErrorKind::Malformed("op".to_string())),
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        let mut object = HashMap::new();
+        let mut insert = |name: &str, value: Value| {
+            object.insert(name.to_string(), value);
+        };
+
+        let (op, path) = match self {
+            Operation::Add { path, value } => {
+                insert("value", value.clone());
+                ("add", path)
+            }
+            Operation::Remove { path } => ("remove", path),
+            Operation::Replace { path, value } => {
+                insert("value", value.clone());
+                ("replace", path)
+            }
+            Operation::Move { from, path } => {
+                insert("from", Value::String(from.clone()));
+                ("move", path)
+            }
+            Operation::Copy { from, path } => {
+                insert("from", Value::String(from.clone()));
+                ("copy", path)
+            }
+            Operation::Test { path, value } => {
+                insert("value", value.clone());
+                ("test", path)
+            }
+        };
+        insert("op", Value::String(op.to_string()));
+        insert("path", Value::String(path.clone()));
+
+        Value::Object(object)
+    }
+
+    fn apply(&self, value: &mut Value) -> Result<(), PatchErrorKind> {
+        match self {
+            Operation::Add { path, value: v } => {
+                value.pointer_insert(path, v.clone())?;
+            }
+            Operation::Remove { path } => {
+                value.pointer_remove(path)?;
+            }
+            Operation::Replace { path, value: v } => {
+                *value.pointer_mut(path)? = v.clone();
+            }
+            Operation::Move { from, path } => {
+                if from == path {
+                    value.pointer(from)?;
+                    return Ok(());
+                }
+                if path.starts_with(from.as_str()) && path[from.len()..].starts_with('/') {
+                    return Err(PatchErrorKind::MoveIntoChild);
+                }
+
+                let v = value.pointer_remove(from)?;
+                value.pointer_insert(path, v)?;
+            }
+            Operation::Copy { from, path } => {
+                let v = value.pointer(from)?.clone();
+                value.pointer_insert(path, v)?;
+            }
+            Operation::Test { path, value: v } => {
+                if !cmp::equals(value.pointer(path)?, v) {
+                    return Err(PatchErrorKind::TestFailed);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl From<PointerError> for PatchErrorKind {
+    fn from(error: PointerError) -> PatchErrorKind {
+        PatchErrorKind::Pointer(error)
+    }
+}
+
+/// Parses a patch document, an array of operation objects.
+pub fn parse_patch(value: &Value) -> Result<Vec<Operation>, PatchError> {
+    match value {
+        Value::Array(array) => array
+            .iter()
+            .enumerate()
+            .map(|(index, op)| Operation::from_value(op).map_err(|kind| PatchError { index, kind }))
+            .collect(),
+        _ => Err(PatchError {
+            index: 0,
+            kind: PatchErrorKind::Malformed("op".to_string()),
+        }),
+    }
+}
+
+/// Applies all operations in order. If any of them fails, `value` is left unchanged.
+pub fn apply_patch(value: &mut Value, patch: &[Operation]) -> Result<(), PatchError> {
+    let mut patched = value.clone();
+
+    for (index, op) in patch.iter().enumerate() {
+        op.apply(&mut patched)
+            .map_err(|kind| PatchError { index, kind })?;
+    }
+
+    *value = patched;
+    Ok(())
+}
+
+/// Operations which turn `from` into `to`.
+///
+/// Object members are diffed recursively, and array elements by their longest common
+/// subsequence so that an insertion or a removal in the middle is a single operation. Arrays too
+/// long for that after their common ends are diffed element by element at the same index.
+pub fn create_patch(from: &Value, to: &Value) -> Vec<Operation> {
+    let mut patch = vec![];
+    diff("", from, to, &mut patch);
+    patch
+}
+
+fn diff(path: &str, from: &Value, to: &Value, patch: &mut Vec<Operation>) {
+    if from == to {
+        return;
+    }
+
+    match (from, to) {
+        (Value::Object(from), Value::Object(to)) => {
+            let mut removed: Vec<&String> = from.keys().filter(|k| !to.contains_key(*k)).collect();
+            removed.sort();
+            for key in removed {
+                patch.push(Operation::Remove {
+                    path: format!("{}/{}", path, pointer::escape(key)),
+                });
+            }
+
+            let mut keys: Vec<&String> = to.keys().collect();
+            keys.sort();
+            for key in keys {
+                let child = format!("{}/{}", path, pointer::escape(key));
+                match from.get(key) {
+                    Some(from) => diff(&child, from, &to[key], patch),
+                    None => patch.push(Operation::Add {
+                        path: child,
+                        value: to[key].clone(),
+                    }),
+                }
+            }
+        }
+        (Value::Array(from), Value::Array(to)) => diff_array(path, from, to, patch),
+        _ => patch.push(Operation::Replace {
+            path: path.to_string(),
+            value: to.clone(),
+        }),
+    }
+}
+
+fn diff_array(path: &str, from: &[Value], to: &[Value], patch: &mut Vec<Operation>) {
+    // Common ends are left as they are, so only the middle needs the table.
+    let prefix = from.iter().zip(to).take_while(|(a, b)| a == b).count();
+    let suffix = from[prefix..]
+        .iter()
+        .rev()
+        .zip(to[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let from = &from[prefix..from.len() - suffix];
+    let to = &to[prefix..to.len() - suffix];
+    let (n, m) = (from.len(), to.len());
+
+    if (n + 1).saturating_mul(m + 1) > MAX_LCS_TABLE {
+        for i in 0..n.min(m) {
+            diff(&format!("{}/{}", path, prefix + i), &from[i], &to[i], patch);
+        }
+        for _ in m..n {
+            patch.push(Operation::Remove {
+                path: format!("{}/{}", path, prefix + m),
+            });
+        }
+        for (j, value) in to.iter().enumerate().skip(n) {
+            patch.push(Operation::Add {
+                path: format!("{}/{}", path, prefix + j),
+                value: value.clone(),
+            });
+        }
+        return;
+    }
+
+    // lcs[i][j] is the length of the LCS of from[i..] and to[j..].
+    let mut lcs = vec![vec![0; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if from[i] == to[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    // `k` is the index in the array being patched.
+    let (mut i, mut j, mut k) = (0, 0, prefix);
+    while i < n || j < m {
+        if i < n && j < m && from[i] == to[j] {
+            i += 1;
+            j += 1;
+            k += 1;
+        } else if i < n && j < m && lcs[i][j] == lcs[i + 1][j + 1] {
+            diff(&format!("{}/{}", path, k), &from[i], &to[j], patch);
+            i += 1;
+            j += 1;
+            k += 1;
+        } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            patch.push(Operation::Remove {
+                path: format!("{}/{}", path, k),
+            });
+            i += 1;
+        } else {
+            patch.push(Operation::Add {
+                path: format!("{}/{}", path, k),
+                value: to[j].clone(),
+            });
+            j += 1;
+            k += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::de::parse;
+
+    fn patch(input: &str) -> Vec<Operation> {
+        parse_patch(&parse(input)).unwrap()
+    }
+
+    #[test]
+    fn test_apply_patch() {
+        // RFC 6902 appendix A
+        let cases = [
+            (
+                r#"{"foo": "bar"}"#,
+                r#"[{"op": "add", "path": "/baz", "value": "qux"}]"#,
+                r#"{"baz": "qux", "foo": "bar"}"#,
+            ),
+            (
+                r#"{"foo": ["bar", "baz"]}"#,
+                r#"[{"op": "add", "path": "/foo/1", "value": "qux"}]"#,
+                r#"{"foo": ["bar", "qux", "baz"]}"#,
+            ),
+            (
+                r#"{"baz": "qux", "foo": "bar"}"#,
+                r#"[{"op": "remove", "path": "/baz"}]"#,
+                r#"{"foo": "bar"}"#,
+            ),
+            (
+                r#"{"foo": ["bar", "qux", "baz"]}"#,
+                r#"[{"op": "remove", "path": "/foo/1"}]"#,
+                r#"{"foo": ["bar", "baz"]}"#,
+            ),
+            (
+                r#"{"baz": "qux", "foo": "bar"}"#,
+                r#"[{"op": "replace", "path": "/baz", "value": "boo"}]"#,
+                r#"{"baz": "boo", "foo": "bar"}"#,
+            ),
+            (
+                r#"{"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}"#,
+                r#"[{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}]"#,
+                r#"{"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred"}}"#,
+            ),
+            (
+                r#"{"foo": ["all", "grass", "cows", "eat"]}"#,
+                r#"[{"op": "move", "from": "/foo/1", "path": "/foo/3"}]"#,
+                r#"{"foo": ["all", "cows", "eat", "grass"]}"#,
+            ),
+            (
+                r#"{"baz": "qux", "foo": ["a", 2, "c"]}"#,
+                r#"[{"op": "test", "path": "/baz", "value": "qux"}, {"op": "test", "path": "/foo/1", "value": 2.0}]"#,
+                r#"{"baz": "qux", "foo": ["a", 2, "c"]}"#,
+            ),
+            (
+                r#"{"foo": "bar"}"#,
+                r#"[{"op": "add", "path": "/child", "value": {"grandchild": {}}}]"#,
+                r#"{"foo": "bar", "child": {"grandchild": {}}}"#,
+            ),
+            (
+                r#"{"foo": ["bar"]}"#,
+                r#"[{"op": "add", "path": "/foo/-", "value": ["abc", "def"]}]"#,
+                r#"{"foo": ["bar", ["abc", "def"]]}"#,
+            ),
+            (
+                r#"{"foo": {"bar": 1}}"#,
+                r#"[{"op": "copy", "from": "/foo", "path": "/baz"}, {"op": "replace", "path": "", "value": [1]}]"#,
+                r#"[1]"#,
+            ),
+        ];
+
+        for (document, ops, expected) in cases.iter() {
+            let mut value = parse(document);
+            apply_patch(&mut value, &patch(ops)).unwrap();
+            assert_eq!(value, parse(expected), "{}", ops);
+        }
+    }
+
+    #[test]
+    fn test_apply_patch_error() {
+        let document = parse(r#"{"foo": {"bar": [1, 2]}, "baz": "qux"}"#);
+        let cases = [
+            (
+                r#"[{"op": "add", "path": "/a", "value": 1}, {"op": "test", "path": "/baz", "value": "bar"}]"#,
+                PatchError {
+                    index: 1,
+                    kind: PatchErrorKind::TestFailed,
+                },
+            ),
+            (
+                r#"[{"op": "remove", "path": "/foo/bar/0"}, {"op": "add", "path": "/baz/bat", "value": 1}]"#,
+                PatchError {
+                    index: 1,
+                    kind: PatchErrorKind::Pointer(PointerError::NotContainer("bat".to_string())),
+                },
+            ),
+            (
+                r#"[{"op": "replace", "path": "/missing", "value": 1}]"#,
+                PatchError {
+                    index: 0,
+                    kind: PatchErrorKind::Pointer(PointerError::MissingKey("missing".to_string())),
+                },
+            ),
+            (
+                r#"[{"op": "add", "path": "/foo/bar/3", "value": 1}]"#,
+                PatchError {
+                    index: 0,
+                    kind: PatchErrorKind::Pointer(PointerError::IndexOutOfBounds(3)),
+                },
+            ),
+            (
+                r#"[{"op": "move", "from": "/foo", "path": "/foo/bar/x"}]"#,
+                PatchError {
+                    index: 0,
+                    kind: PatchErrorKind::MoveIntoChild,
+                },
+            ),
+        ];
+
+        for (ops, error) in cases.iter() {
+            let mut value = document.clone();
+            assert_eq!(
+                &apply_patch(&mut value, &patch(ops)).unwrap_err(),
+                error,
+                "{}",
+                ops
+            );
+            assert_eq!(value, document);
+        }
+    }
+
+    #[test]
+    fn test_parse_patch() {
+        assert_eq!(
+            parse_patch(&parse(
+                r#"[{"op": "remove", "path": "/a"}, {"op": "add", "path": "/a"}]"#
+            )),
+            Err(PatchError {
+                index: 1,
+                kind: PatchErrorKind::Malformed("value".to_string()),
+            })
+        );
+        assert_eq!(
+            Operation::from_value(&parse(r#"{"op": "frob", "path": "/a"}"#)),
+            Err(PatchErrorKind::Malformed("op".to_string()))
+        );
+        assert_eq!(
+            Operation::from_value(&parse(r#"{"op": "move", "from": 1, "path": "/a"}"#)),
+            Err(PatchErrorKind::Malformed("from".to_string()))
+        );
+
+        let ops = patch(
+            r#"[{"op": "copy", "from": "/a", "path": "/b"}, {"op": "test", "path": "", "value": null}]"#,
+        );
+        let value = Value::Array(ops.iter().map(|op| op.to_value()).collect());
+        assert_eq!(parse_patch(&value), Ok(ops));
+    }
+
+    #[test]
+    fn test_create_patch() {
+        let cases = [
+            (r#"{"a": 1}"#, r#"{"a": 1}"#, "[]"),
+            (
+                r#"{"a": 1, "b": {"c": 2, "d": 3}}"#,
+                r#"{"b": {"c": 2, "d": 4, "e/f": 5}}"#,
+                r#"[
+                  {"op": "remove", "path": "/a"},
+                  {"op": "replace", "path": "/b/d", "value": 4},
+                  {"op": "add", "path": "/b/e~1f", "value": 5}
+                ]"#,
+            ),
+            (
+                "[1, 2, 3, 4]",
+                "[1, 3, 4, 5]",
+                r#"[
+                  {"op": "remove", "path": "/1"},
+                  {"op": "add", "path": "/3", "value": 5}
+                ]"#,
+            ),
+            (
+                "[1, 2, 3]",
+                "[0, 1, 2, 3]",
+                r#"[{"op": "add", "path": "/0", "value": 0}]"#,
+            ),
+            (
+                r#"[{"a": 1}, 2]"#,
+                r#"[{"a": 2}, 2]"#,
+                r#"[{"op": "replace", "path": "/0/a", "value": 2}]"#,
+            ),
+            (
+                r#"{"a": [1]}"#,
+                r#"{"a": "x"}"#,
+                r#"[{"op": "replace", "path": "/a", "value": "x"}]"#,
+            ),
+            (
+                "1",
+                "1.0",
+                r#"[{"op": "replace", "path": "", "value": 1.0}]"#,
+            ),
+        ];
+
+        for (from, to, expected) in cases.iter() {
+            let (from, to) = (parse(from), parse(to));
+            let ops = create_patch(&from, &to);
+            assert_eq!(ops, patch(expected));
+
+            let mut value = from.clone();
+            apply_patch(&mut value, &ops).unwrap();
+            assert_eq!(value, to);
+        }
+    }
+
+    #[test]
+    fn test_create_patch_large() {
+        let array = |range: std::ops::Range<usize>| {
+            Value::Array(range.map(|i| Value::Number(i.to_string())).collect())
+        };
+
+        // Only the middle, between the common ends, is diffed.
+        let from = array(0..100_000);
+        let mut to = from.clone();
+        if let Value::Array(elements) = &mut to {
+            elements.remove(50_000);
+        }
+        assert_eq!(
+            create_patch(&from, &to),
+            vec![Operation::Remove {
+                path: "/50000".to_string()
+            }]
+        );
+
+        // Elements at the same index, beyond the size of the table.
+        for (from, to) in [(0..2000, 5000..8000), (0..3000, 5000..7000)].iter() {
+            let (from, to) = (array(from.clone()), array(to.clone()));
+            let ops = create_patch(&from, &to);
+            assert_eq!(ops.len(), 3000);
+
+            let mut value = from.clone();
+            apply_patch(&mut value, &ops).unwrap();
+            assert_eq!(value, to);
+        }
+    }
+}
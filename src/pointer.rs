@@ -0,0 +1,355 @@
+/// JSON Pointer (RFC 6901)
+///
+/// - ""            the whole document
+/// - "/servers/0"  the first element of "servers"
+/// - "/a~1b/c~0d"  "c~d" of "a/b"
+use std::fmt;
+
+use crate::de::Value;
+
+#[derive(Eq, PartialEq, Debug)]
+pub enum PointerError {
+    /// Neither empty nor starts with "/", or has an invalid "~" escape.
+    Malformed,
+    MissingKey(String),
+    IndexOutOfBounds(usize),
+    /// A reference token to an array which is not an index.
+    InvalidIndex(String),
+    /// A reference token to a string, number, boolean or null.
+    NotContainer(String),
+}
+
+impl fmt::Display for PointerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PointerError::Malformed => write!(f, "malformed JSON Pointer"),
+            PointerError::MissingKey(key) => write!(f, "no member \"{}\"", key),
+            PointerError::IndexOutOfBounds(index) => write!(f, "index {} is out of bounds", index),
+            PointerError::InvalidIndex(token) => write!(f, "\"{}\" is not an array index", token),
+            PointerError::NotContainer(token) => {
+                write!(f, "\"{}\" of a value which is not a container", token)
+            }
+        }
+    }
+}
+
+impl Value {
+    pub fn pointer(&self, pointer: &str) -> Result<&Value, PointerError> {
+        let mut value = self;
+
+        for token in tokens(pointer)? {
+            value = match value {
+                Value::Object(object) => {
+                    object.get(&token).ok_or(PointerError::MissingKey(token))?
+                }
+                Value::Array(array) => {
+                    let index = index(&token, array.len())?;
+                    array
+                        .get(index)
+                        .ok_or(PointerError::IndexOutOfBounds(index))?
+                }
+                _ => return Err(PointerError::NotContainer(token)),
+            };
+        }
+
+        Ok(value)
+    }
+
+    pub fn pointer_mut(&mut self, pointer: &str) -> Result<&mut Value, PointerError> {
+        let mut value = self;
+
+        for token in tokens(pointer)? {
+            value = match value {
+                Value::Object(object) => match object.get_mut(&token) {
+                    Some(value) => value,
+                    None => return Err(PointerError::MissingKey(token)),
+                },
+                Value::Array(array) => {
+                    let index = index(&token, array.len())?;
+                    array
+                        .get_mut(index)
+                        .ok_or(PointerError::IndexOutOfBounds(index))?
+                }
+                _ => return Err(PointerError::NotContainer(token)),
+            };
+        }
+
+        Ok(value)
+    }
+
+    /// Adds `value` at `pointer`, and returns the replaced value if any.
+    ///
+    /// The parent must exist. A member of an object is added or replaced, and an element is
+    /// inserted into an array before the index, or appended with "-". The empty pointer replaces
+    /// the whole value.
+    pub fn pointer_insert(
+        &mut self,
+        pointer: &str,
+        value: Value,
+    ) -> Result<Option<Value>, PointerError> {
+        let (parent, last) = match split_last(pointer)? {
+            Some(split) => split,
+            None => return Ok(Some(std::mem::replace(self, value))),
+        };
+
+        match self.pointer_mut(parent)? {
+            Value::Object(object) => Ok(object.insert(last, value)),
+            Value::Array(array) => {
+                let index = index(&last, array.len())?;
+                if index > array.len() {
+                    return Err(PointerError::IndexOutOfBounds(index));
+                }
+
+                array.insert(index, value);
+                Ok(None)
+            }
+            _ => Err(PointerError::NotContainer(last)),
+        }
+    }
+
+    /// Removes the value at `pointer` and returns it. The empty pointer leaves `Null` behind.
+    pub fn pointer_remove(&mut self, pointer: &str) -> Result<Value, PointerError> {
+        let (parent, last) = match split_last(pointer)? {
+            Some(split) => split,
+            None => return Ok(std::mem::replace(self, Value::Null)),
+        };
+
+        match self.pointer_mut(parent)? {
+            Value::Object(object) => object.remove(&last).ok_or(PointerError::MissingKey(last)),
+            Value::Array(array) => {
+                let index = index(&last, array.len())?;
+                if index >= array.len() {
+                    return Err(PointerError::IndexOutOfBounds(index));
+                }
+
+                Ok(array.remove(index))
+            }
+            _ => Err(PointerError::NotContainer(last)),
+        }
+    }
+}
+
+/// Unescaped reference tokens of a pointer.
+pub(crate) fn tokens(pointer: &str) -> Result<Vec<String>, PointerError> {
+    if pointer.is_empty() {
+        return Ok(vec![]);
+    }
+
+    if !pointer.starts_with('/') {
+        return Err(PointerError::Malformed);
+    }
+
+    pointer[1..].split('/').map(unescape).collect()
+}
+
+/// Escapes a key to be a reference token.
+pub(crate) fn escape(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape(token: &str) -> Result<String, PointerError> {
+    let mut val = "".to_string();
+    let mut chars = token.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '~' => match chars.next() {
+                Some('0') => val.push('~'),
+                Some('1') => val.push('/'),
+                _ => return Err(PointerError::Malformed),
+            },
+            c => val.push(c),
+        }
+    }
+
+    Ok(val)
+}
+
+/// Splits a pointer into the parent pointer and the unescaped last token.
+fn split_last(pointer: &str) -> Result<Option<(&str, String)>, PointerError> {
+    if pointer.is_empty() {
+        return Ok(None);
+    }
+
+    match pointer.rfind('/') {
+        Some(i) if pointer.starts_with('/') => {
+            Ok(Some((&pointer[..i], unescape(&pointer[i + 1..])?)))
+        }
+        _ => Err(PointerError::Malformed),
+    }
+}
+
+/// "-" is the index after the last element.
+fn index(token: &str, len: usize) -> Result<usize, PointerError> {
+    match token {
+        "-" => Ok(len),
+        _ => parse_index(token).ok_or_else(|| PointerError::InvalidIndex(token.to_string())),
+    }
+}
+
+/// "0" or digits without leading zeros.
+pub(crate) fn parse_index(s: &str) -> Option<usize> {
+    if s.is_empty() || (s.len() > 1 && s.starts_with('0')) || !s.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+
+    s.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::de::parse;
+
+    #[test]
+    fn test_value_pointer() {
+        // RFC 6901 section 5
+        let value = parse(
+            r#"
+{
+  "foo": ["bar", "baz"],
+  "": 0,
+  "a/b": 1,
+  "c%d": 2,
+  "e^f": 3,
+  "g|h": 4,
+  "i\\j": 5,
+  "k\"l": 6,
+  " ": 7,
+  "m~n": 8
+}
+"#,
+        );
+
+        assert_eq!(value.pointer(""), Ok(&value));
+        assert_eq!(
+            value.pointer("/foo"),
+            Ok(&Value::Array(vec![
+                Value::String("bar".to_string()),
+                Value::String("baz".to_string())
+            ]))
+        );
+        assert_eq!(
+            value.pointer("/foo/0"),
+            Ok(&Value::String("bar".to_string()))
+        );
+        let cases = [
+            ("/", "0"),
+            ("/a~1b", "1"),
+            ("/c%d", "2"),
+            ("/e^f", "3"),
+            ("/g|h", "4"),
+            ("/i\\j", "5"),
+            ("/k\"l", "6"),
+            ("/ ", "7"),
+            ("/m~0n", "8"),
+        ];
+        for (pointer, n) in cases.iter() {
+            assert_eq!(value.pointer(pointer), Ok(&Value::Number(n.to_string())));
+        }
+    }
+
+    #[test]
+    fn test_value_pointer_error() {
+        let value = parse(r#"{"foo": ["bar"], "~01": {}}"#);
+
+        assert_eq!(value.pointer("foo"), Err(PointerError::Malformed));
+        assert_eq!(value.pointer("/m~2n"), Err(PointerError::Malformed));
+        assert_eq!(value.pointer("/m~"), Err(PointerError::Malformed));
+        assert_eq!(
+            value.pointer("/~01"),
+            Err(PointerError::MissingKey("~1".to_string()))
+        );
+        assert_eq!(value.pointer("/~001"), Ok(&parse("{}")));
+        assert_eq!(
+            value.pointer("/bar"),
+            Err(PointerError::MissingKey("bar".to_string()))
+        );
+        assert_eq!(
+            value.pointer("/foo/1"),
+            Err(PointerError::IndexOutOfBounds(1))
+        );
+        assert_eq!(
+            value.pointer("/foo/-"),
+            Err(PointerError::IndexOutOfBounds(1))
+        );
+        assert_eq!(
+            value.pointer("/foo/01"),
+            Err(PointerError::InvalidIndex("01".to_string()))
+        );
+        assert_eq!(
+            value.pointer("/foo/0/x"),
+            Err(PointerError::NotContainer("x".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_value_pointer_mut() {
+        let mut value = parse(r#"{"servers": [{"host": "a"}]}"#);
+
+        *value.pointer_mut("/servers/0/host").unwrap() = Value::String("b".to_string());
+        assert_eq!(value, parse(r#"{"servers": [{"host": "b"}]}"#));
+        assert_eq!(
+            value.pointer_mut("/servers/1/host"),
+            Err(PointerError::IndexOutOfBounds(1))
+        );
+    }
+
+    #[test]
+    fn test_value_pointer_insert() {
+        let mut value = parse(r#"{"a": [1, 3]}"#);
+
+        assert_eq!(value.pointer_insert("/b", Value::Null), Ok(None));
+        assert_eq!(
+            value.pointer_insert("/b", Value::Bool(true)),
+            Ok(Some(Value::Null))
+        );
+        assert_eq!(value.pointer_insert("/a/1", parse("2")), Ok(None));
+        assert_eq!(value.pointer_insert("/a/-", parse("4")), Ok(None));
+        assert_eq!(value, parse(r#"{"a": [1, 2, 3, 4], "b": true}"#));
+
+        assert_eq!(
+            value.pointer_insert("/a/5", Value::Null),
+            Err(PointerError::IndexOutOfBounds(5))
+        );
+        assert_eq!(
+            value.pointer_insert("/c/d", Value::Null),
+            Err(PointerError::MissingKey("c".to_string()))
+        );
+        assert_eq!(
+            value.pointer_insert("/b/c", Value::Null),
+            Err(PointerError::NotContainer("c".to_string()))
+        );
+        assert_eq!(
+            value.pointer_insert("a", Value::Null),
+            Err(PointerError::Malformed)
+        );
+
+        assert_eq!(
+            value.pointer_insert("", Value::Null),
+            Ok(Some(parse(r#"{"a": [1, 2, 3, 4], "b": true}"#)))
+        );
+        assert_eq!(value, Value::Null);
+    }
+
+    #[test]
+    fn test_value_pointer_remove() {
+        let mut value = parse(r#"{"a": [1, 2, 3], "b/c": true}"#);
+
+        assert_eq!(value.pointer_remove("/b~1c"), Ok(Value::Bool(true)));
+        assert_eq!(value.pointer_remove("/a/1"), Ok(parse("2")));
+        assert_eq!(value, parse(r#"{"a": [1, 3]}"#));
+
+        assert_eq!(
+            value.pointer_remove("/a/-"),
+            Err(PointerError::IndexOutOfBounds(2))
+        );
+        assert_eq!(
+            value.pointer_remove("/b"),
+            Err(PointerError::MissingKey("b".to_string()))
+        );
+        assert_eq!(value.pointer_remove(""), Ok(parse(r#"{"a": [1, 3]}"#)));
+        assert_eq!(value, Value::Null);
+    }
+}
@@ -0,0 +1,113 @@
+/// Raw values
+///
+/// {"id": 1, "payload": {"a": [1.50, "\n"]}}
+///
+/// A raw value keeps the input text of a value, such as `{"a": [1.50, "\n"]}` of "payload" above,
+/// without building it. Its syntax is checked, and it is written back exactly as it was by its
+/// `Display` or `JsonWriter::raw`.
+use std::fmt;
+
+use crate::de::{Deserializer, Value};
+use crate::token;
+
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct RawValue {
+    json: String,
+}
+
+impl RawValue {
+    /// Checks that `json` is a single value, and keeps it without the surrounding whitespaces.
+    pub fn from_string(json: String) -> Result<RawValue, token::Error> {
+        let raw = Deserializer::new(&json).parse_raw()?;
+        Ok(raw.expect("a raw value"))
+    }
+
+    /// `json` must be a single value without surrounding whitespaces.
+    pub(crate) fn from_checked(json: String) -> RawValue {
+        RawValue { json }
+    }
+
+    /// The input text as it was.
+    pub fn get(&self) -> &str {
+        &self.json
+    }
+
+    pub fn into_string(self) -> String {
+        self.json
+    }
+
+    /// Builds the value.
+    pub fn parse(&self) -> Value {
+        let value = Deserializer::new(&self.json).parse();
+        value.expect("a checked value").expect("a value")
+    }
+
+    /// Members with their values still raw, or `None` if not an object.
+    pub fn members(&self) -> Option<Vec<(String, RawValue)>> {
+        let members = Deserializer::new(&self.json).parse_raw_members();
+        members.expect("a checked value")
+    }
+
+    /// Elements still raw, or `None` if not an array.
+    pub fn elements(&self) -> Option<Vec<RawValue>> {
+        let elements = Deserializer::new(&self.json).parse_raw_elements();
+        elements.expect("a checked value")
+    }
+}
+
+/// The input text as it was.
+impl fmt::Display for RawValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::writer::JsonWriter;
+
+    #[test]
+    fn test_raw_value() {
+        let input = " {\"id\": 1, \"payload\": {\"a\": [1.50, \"\\n\"]},\n \"tags\": [ ] } ";
+        let raw = Deserializer::new(input).parse_raw().unwrap().unwrap();
+        assert_eq!(raw.get(), input.trim());
+        assert_eq!(
+            raw.parse(),
+            Deserializer::new(input).parse().unwrap().unwrap()
+        );
+
+        let members = raw.members().unwrap();
+        let keys: Vec<&str> = members.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["id", "payload", "tags"]);
+        assert_eq!(members[1].1.get(), r#"{"a": [1.50, "\n"]}"#);
+        assert_eq!(members[2].1.to_string(), "[ ]");
+        assert_eq!(members[2].1.elements(), Some(vec![]));
+        assert_eq!(members[0].1.members(), None);
+
+        let elements = RawValue::from_string(" [1.0 , {} ,\"x\"] ".to_string())
+            .unwrap()
+            .elements()
+            .unwrap();
+        let elements: Vec<&str> = elements.iter().map(RawValue::get).collect();
+        assert_eq!(elements, vec!["1.0", "{}", "\"x\""]);
+
+        let mut writer = JsonWriter::new(vec![]);
+        writer.begin_object().unwrap();
+        writer.key("forwarded").unwrap();
+        writer.raw(&members[1].1).unwrap();
+        writer.end_object().unwrap();
+        assert_eq!(
+            writer.finish().unwrap(),
+            br#"{"forwarded":{"a": [1.50, "\n"]}}"#.to_vec()
+        );
+
+        for input in ["", "[1,]", "{\"a\" 1}", "[1] 2", "{\"a\": tru}"].iter() {
+            assert!(
+                RawValue::from_string(input.to_string()).is_err(),
+                "{}",
+                input
+            );
+        }
+    }
+}
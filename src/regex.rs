@@ -0,0 +1,725 @@
+// I-Regexp (RFC 9485)
+//
+// A pattern compiles into a Thompson NFA, which runs over the input once in all of its states at
+// the same time. So matching takes time linear in the length of the input, whatever the pattern.
+//
+// - branches:    a|b
+// - quantifiers: * + ? {n} {n,} {n,m}
+// - atoms:       . (a) [a-z] [^a-z] \n \. \p{L} \P{Nd}
+//
+// JSON Schema patterns are ECMA-262 ones, of which the common extensions are supported too:
+// `^` and `$` anchors, `\d` `\w` `\s` and their negations `\D` `\W` `\S`, and `(?:a)`.
+//
+// `\p{..}` takes the general categories I-Regexp allows, such as L, Lu, N or Nd, which are looked
+// up in the tables of `unicode`.
+//
+// Patterns nested more than `MAX_NESTING` deep, or compiling into more than `MAX_PROGRAM`
+// instructions, such as `a{100000}`, are rejected as invalid.
+
+const MAX_NESTING: usize = 64;
+const MAX_PROGRAM: usize = 10_000;
+
+/// Alternatives of sequences.
+#[derive(Debug)]
+struct Alternation(Vec<Vec<Piece>>);
+
+#[derive(Debug)]
+struct Piece {
+    atom: Atom,
+    min: usize,
+    max: Option<usize>,
+}
+
+#[derive(Debug)]
+enum Atom {
+    Class(Class),
+    Group(Alternation),
+    /// `^`, only in ECMA-262 patterns.
+    Start,
+    /// `$`, only in ECMA-262 patterns.
+    End,
+}
+
+#[derive(Clone, Debug)]
+enum Class {
+    Char(char),
+    /// Any character but \n and \r.
+    Dot,
+    Category(Category, bool),
+    /// Items, and whether it is negated.
+    Set(Vec<Item>, bool),
+}
+
+#[derive(Clone, Debug)]
+enum Item {
+    Range(char, char),
+    Category(Category, bool),
+    /// `\d`, `\w` or `\s` as ranges, and whether it is negated.
+    Set(Vec<Item>, bool),
+}
+
+/// General categories, as a bit per `GeneralCategory`.
+#[derive(Clone, Copy, Debug)]
+struct Category(u32);
+
+#[derive(Debug)]
+pub(crate) struct Regex {
+    program: Vec<Inst>,
+}
+
+/// An instruction of the compiled automaton.
+#[derive(Debug)]
+enum Inst {
+    /// Eats a character in the class.
+    Class(Class),
+    /// Goes on at both.
+    Split(usize, usize),
+    Jump(usize),
+    /// `^`
+    Start,
+    /// `$`
+    End,
+    Match,
+}
+
+impl Regex {
+    pub(crate) fn new(pattern: &str) -> Option<Regex> {
+        Regex::parse(pattern, false)
+    }
+
+    /// Like `new`, but with the extensions of the ECMA-262 patterns of JSON Schema. `^` and `$`
+    /// match the start and the end of the input.
+    pub(crate) fn ecma(pattern: &str) -> Option<Regex> {
+        Regex::parse(pattern, true)
+    }
+
+    fn parse(pattern: &str, ecma: bool) -> Option<Regex> {
+        let mut parser = Parser {
+            chars: pattern.chars().collect(),
+            pos: 0,
+            ecma,
+            nesting: 0,
+        };
+        let alternation = parser.alternation()?;
+
+        if parser.pos != parser.chars.len() {
+            return None;
+        }
+
+        let mut compiler = Compiler { program: vec![] };
+        compiler.alternation(&alternation)?;
+        compiler.push(Inst::Match)?;
+
+        Some(Regex {
+            program: compiler.program,
+        })
+    }
+
+    /// Whether the whole input matches.
+    pub(crate) fn is_match(&self, input: &str) -> bool {
+        self.run(input, false)
+    }
+
+    /// Whether any substring of the input matches.
+    pub(crate) fn search(&self, input: &str) -> bool {
+        self.run(input, true)
+    }
+
+    /// Steps all the threads one character at a time. A search starts a new thread at every
+    /// position, and succeeds as soon as any thread matches.
+    fn run(&self, input: &str, search: bool) -> bool {
+        let length = input.chars().count();
+        let mut current = Threads::new(self.program.len());
+        let mut next = Threads::new(self.program.len());
+        current.add(&self.program, 0, 0, length);
+
+        for (pos, c) in input.chars().enumerate() {
+            if search && current.matched {
+                return true;
+            }
+
+            next.clear();
+            for &pc in &current.pcs {
+                match &self.program[pc] {
+                    Inst::Class(class) if class.contains(c) => {
+                        next.add(&self.program, pc + 1, pos + 1, length)
+                    }
+                    _ => {}
+                }
+            }
+            if search {
+                next.add(&self.program, 0, pos + 1, length);
+            }
+            std::mem::swap(&mut current, &mut next);
+
+            // Nothing more can match.
+            if current.pcs.is_empty() {
+                return current.matched && (search || pos + 1 == length);
+            }
+        }
+
+        current.matched
+    }
+}
+
+/// The threads at a position, each of which waits at an `Inst::Class`.
+struct Threads {
+    pcs: Vec<usize>,
+    /// The generation in which each instruction was last added, so that each is added once.
+    added: Vec<usize>,
+    generation: usize,
+    /// Whether any thread has reached `Inst::Match`.
+    matched: bool,
+}
+
+impl Threads {
+    fn new(len: usize) -> Threads {
+        Threads {
+            pcs: vec![],
+            added: vec![0; len],
+            generation: 1,
+            matched: false,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.pcs.clear();
+        self.generation += 1;
+        self.matched = false;
+    }
+
+    /// Adds a thread at `pc`, following jumps, splits and anchors without eating anything.
+    fn add(&mut self, program: &[Inst], pc: usize, pos: usize, length: usize) {
+        let mut stack = vec![pc];
+
+        while let Some(pc) = stack.pop() {
+            if self.added[pc] == self.generation {
+                continue;
+            }
+            self.added[pc] = self.generation;
+
+            match program[pc] {
+                Inst::Class(_) => self.pcs.push(pc),
+                Inst::Split(first, second) => {
+                    stack.push(second);
+                    stack.push(first);
+                }
+                Inst::Jump(to) => stack.push(to),
+                Inst::Start if pos == 0 => stack.push(pc + 1),
+                Inst::End if pos == length => stack.push(pc + 1),
+                Inst::Start | Inst::End => {}
+                Inst::Match => self.matched = true,
+            }
+        }
+    }
+}
+
+struct Compiler {
+    program: Vec<Inst>,
+}
+
+impl Compiler {
+    fn push(&mut self, inst: Inst) -> Option<usize> {
+        if self.program.len() >= MAX_PROGRAM {
+            return None;
+        }
+
+        self.program.push(inst);
+        Some(self.program.len() - 1)
+    }
+
+    /// Points the split at `at`, which has been pushed as a placeholder, to the next instruction
+    /// and `to`.
+    fn patch_split(&mut self, at: usize, to: usize) {
+        self.program[at] = Inst::Split(at + 1, to);
+    }
+
+    fn alternation(&mut self, alternation: &Alternation) -> Option<()> {
+        let (last, branches) = alternation.0.split_last()?;
+        let mut jumps = vec![];
+
+        for branch in branches {
+            let split = self.push(Inst::Split(0, 0))?;
+            self.sequence(branch)?;
+            jumps.push(self.push(Inst::Jump(0))?);
+            self.patch_split(split, self.program.len());
+        }
+        self.sequence(last)?;
+
+        let end = self.program.len();
+        for jump in jumps {
+            self.program[jump] = Inst::Jump(end);
+        }
+        Some(())
+    }
+
+    fn sequence(&mut self, pieces: &[Piece]) -> Option<()> {
+        pieces.iter().try_for_each(|piece| self.piece(piece))
+    }
+
+    fn piece(&mut self, piece: &Piece) -> Option<()> {
+        // Even an atom which compiles into nothing can not be repeated that many times.
+        if piece.min.max(piece.max.unwrap_or(0)) > MAX_PROGRAM {
+            return None;
+        }
+
+        for _ in 0..piece.min {
+            self.atom(&piece.atom)?;
+        }
+
+        match piece.max {
+            None => {
+                let split = self.push(Inst::Split(0, 0))?;
+                self.atom(&piece.atom)?;
+                self.push(Inst::Jump(split))?;
+                self.patch_split(split, self.program.len());
+            }
+            Some(max) => {
+                let mut splits = vec![];
+                for _ in piece.min..max {
+                    splits.push(self.push(Inst::Split(0, 0))?);
+                    self.atom(&piece.atom)?;
+                }
+
+                let end = self.program.len();
+                for split in splits {
+                    self.patch_split(split, end);
+                }
+            }
+        }
+        Some(())
+    }
+
+    fn atom(&mut self, atom: &Atom) -> Option<()> {
+        match atom {
+            Atom::Class(class) => self.push(Inst::Class(class.clone())).map(|_| ()),
+            Atom::Group(alternation) => self.alternation(alternation),
+            Atom::Start => self.push(Inst::Start).map(|_| ()),
+            Atom::End => self.push(Inst::End).map(|_| ()),
+        }
+    }
+}
+
+impl Class {
+    fn contains(&self, c: char) -> bool {
+        match self {
+            Class::Char(ch) => *ch == c,
+            Class::Dot => c != '\n' && c != '\r',
+            Class::Category(category, negated) => category.contains(c) != *negated,
+            Class::Set(items, negated) => items.iter().any(|item| item.contains(c)) != *negated,
+        }
+    }
+}
+
+impl Item {
+    fn contains(&self, c: char) -> bool {
+        match self {
+            Item::Range(start, end) => *start <= c && c <= *end,
+            Item::Category(category, negated) => category.contains(c) != *negated,
+            Item::Set(items, negated) => items.iter().any(|item| item.contains(c)) != *negated,
+        }
+    }
+}
+
+impl Category {
+    /// A category such as "Lu", or all of those in a major one such as "L". Surrogates (Cs) are
+    /// not allowed.
+    fn from_name(name: &str) -> Option<Category> {
+        let mask = crate::unicode::GeneralCategory::ALL
+            .iter()
+            .filter(|(_, n)| match name.len() {
+                1 => n.starts_with(name) && *n != "Cs",
+                _ => *n == name && name != "Cs",
+            })
+            .fold(0, |mask, (category, _)| mask | 1 << *category as u32);
+
+        match mask {
+            0 => None,
+            _ => Some(Category(mask)),
+        }
+    }
+
+    fn contains(self, c: char) -> bool {
+        self.0 & 1 << crate::unicode::GeneralCategory::of(c) as u32 != 0
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    /// Whether the ECMA-262 extensions are allowed.
+    ecma: bool,
+    /// The number of groups around the current position.
+    nesting: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn one(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    fn eatc(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn alternation(&mut self) -> Option<Alternation> {
+        let mut branches = vec![self.branch()?];
+
+        while self.eatc('|') {
+            branches.push(self.branch()?);
+        }
+
+        Some(Alternation(branches))
+    }
+
+    fn branch(&mut self) -> Option<Vec<Piece>> {
+        let mut pieces = vec![];
+
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+
+            let atom = self.atom()?;
+            let (min, max) = self.quantifier()?;
+            pieces.push(Piece { atom, min, max });
+        }
+
+        Some(pieces)
+    }
+
+    fn quantifier(&mut self) -> Option<(usize, Option<usize>)> {
+        match self.peek() {
+            Some('*') => {
+                self.one();
+                Some((0, None))
+            }
+            Some('+') => {
+                self.one();
+                Some((1, None))
+            }
+            Some('?') => {
+                self.one();
+                Some((0, Some(1)))
+            }
+            Some('{') => {
+                self.one();
+                let min = self.integer()?;
+                let max = if self.eatc(',') {
+                    match self.peek() {
+                        Some('}') => None,
+                        _ => Some(self.integer()?),
+                    }
+                } else {
+                    Some(min)
+                };
+
+                if !self.eatc('}') || max.is_some_and(|max| max < min) {
+                    return None;
+                }
+                Some((min, max))
+            }
+            _ => Some((1, Some(1))),
+        }
+    }
+
+    fn integer(&mut self) -> Option<usize> {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if !c.is_ascii_digit() {
+                break;
+            }
+            self.one();
+        }
+
+        self.chars[start..self.pos]
+            .iter()
+            .collect::<String>()
+            .parse()
+            .ok()
+    }
+
+    fn atom(&mut self) -> Option<Atom> {
+        match self.one()? {
+            '(' => {
+                self.nesting += 1;
+                if self.nesting > MAX_NESTING {
+                    return None;
+                }
+
+                // A non-capturing group is the same as a group, since groups capture nothing.
+                if self.ecma && self.peek() == Some('?') {
+                    self.one();
+                    if !self.eatc(':') {
+                        return None;
+                    }
+                }
+
+                let alternation = self.alternation()?;
+                if !self.eatc(')') {
+                    return None;
+                }
+                self.nesting -= 1;
+                Some(Atom::Group(alternation))
+            }
+            '.' => Some(Atom::Class(Class::Dot)),
+            '^' if self.ecma => Some(Atom::Start),
+            '$' if self.ecma => Some(Atom::End),
+            '[' => self.set().map(Atom::Class),
+            '\\' => match self.escape()? {
+                Item::Range(c, _) => Some(Atom::Class(Class::Char(c))),
+                Item::Category(category, negated) => {
+                    Some(Atom::Class(Class::Category(category, negated)))
+                }
+                Item::Set(items, negated) => Some(Atom::Class(Class::Set(items, negated))),
+            },
+            '*' | '+' | '?' | '{' | '}' | ']' | ')' => None,
+            c => Some(Atom::Class(Class::Char(c))),
+        }
+    }
+
+    /// Assumes that the backslash has been already eaten.
+    fn escape(&mut self) -> Option<Item> {
+        let c = match self.one()? {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            c @ 'p' | c @ 'P' => {
+                if !self.eatc('{') {
+                    return None;
+                }
+                let start = self.pos;
+                while self.peek()? != '}' {
+                    self.one();
+                }
+                let name: String = self.chars[start..self.pos].iter().collect();
+                self.one();
+
+                return Some(Item::Category(Category::from_name(&name)?, c == 'P'));
+            }
+            c @ 'd' | c @ 'D' if self.ecma => {
+                return Some(Item::Set(vec![Item::Range('0', '9')], c == 'D'));
+            }
+            c @ 'w' | c @ 'W' if self.ecma => {
+                let items = vec![
+                    Item::Range('0', '9'),
+                    Item::Range('A', 'Z'),
+                    Item::Range('_', '_'),
+                    Item::Range('a', 'z'),
+                ];
+                return Some(Item::Set(items, c == 'W'));
+            }
+            // WhiteSpace and LineTerminator of ECMA-262.
+            c @ 's' | c @ 'S' if self.ecma => {
+                let items = vec![
+                    Item::Range('\t', '\r'),
+                    Item::Range(' ', ' '),
+                    Item::Range('\u{a0}', '\u{a0}'),
+                    Item::Range('\u{1680}', '\u{1680}'),
+                    Item::Range('\u{2000}', '\u{200a}'),
+                    Item::Range('\u{2028}', '\u{2029}'),
+                    Item::Range('\u{202f}', '\u{202f}'),
+                    Item::Range('\u{205f}', '\u{205f}'),
+                    Item::Range('\u{3000}', '\u{3000}'),
+                    Item::Range('\u{feff}', '\u{feff}'),
+                ];
+                return Some(Item::Set(items, c == 'S'));
+            }
+            c @ '('..='+' | c @ '-' | c @ '.' | c @ '?' | c @ '['..='^' | c @ '{'..='}' => c,
+            _ => return None,
+        };
+
+        Some(Item::Range(c, c))
+    }
+
+    /// Assumes that the left bracket has been already eaten.
+    fn set(&mut self) -> Option<Class> {
+        let negated = self.eatc('^');
+        let mut items = vec![];
+
+        loop {
+            let start = match self.one()? {
+                ']' if items.is_empty() => return None,
+                ']' => break,
+                '\\' => match self.escape()? {
+                    Item::Range(c, _) => c,
+                    class => {
+                        items.push(class);
+                        continue;
+                    }
+                },
+                '[' => return None,
+                c => c,
+            };
+
+            // A range unless "-" is the last character.
+            if self.peek() == Some('-') && self.chars.get(self.pos + 1) != Some(&']') {
+                self.one();
+                let end = match self.one()? {
+                    '\\' => match self.escape()? {
+                        Item::Range(c, _) => c,
+                        _ => return None,
+                    },
+                    '[' | ']' => return None,
+                    c => c,
+                };
+                if end < start {
+                    return None;
+                }
+                items.push(Item::Range(start, end));
+            } else {
+                items.push(Item::Range(start, start));
+            }
+        }
+
+        Some(Class::Set(items, negated))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regex_is_match() {
+        let cases = [
+            ("abc", "abc", true),
+            ("abc", "abcd", false),
+            ("a.c", "a-c", true),
+            ("a.c", "a\nc", false),
+            ("a*", "", true),
+            ("a*", "aaaa", true),
+            ("a+b", "b", false),
+            ("a+b", "aab", true),
+            ("colou?r", "color", true),
+            ("a{2,3}", "aaa", true),
+            ("a{2,3}", "aaaa", false),
+            ("a{2,}", "aaaaa", true),
+            ("a{2}", "a", false),
+            ("(ab|cd)+", "abcdab", true),
+            ("(a|ab)c", "abc", true),
+            ("[a-c]+", "abcab", true),
+            ("[^a-c]", "d", true),
+            ("[^a-c]", "a", false),
+            ("[a-]", "-", true),
+            ("[\\-\\]]+", "-]", true),
+            ("\\.\\*", ".*", true),
+            ("\\p{Lu}\\p{Ll}+", "Hello", true),
+            ("\\P{L}", "1", true),
+            ("\\p{P}", "+", false),
+            ("\\p{P}+", "¿「」", true),
+            ("\\p{S}\\p{Sc}", "+€", true),
+            ("\\p{Nd}", "٣", true),
+            ("\\p{Nd}", "Ⅳ", false),
+            ("\\p{Nl}", "Ⅳ", true),
+            ("\\p{Lo}\\p{Lt}", "日ǅ", true),
+            ("\\p{Lu}", "ǅ", false),
+            ("\\p{M}", "\u{301}", true),
+            ("[\\p{Zs}\\p{Cc}]+", "\u{a0}\t", true),
+            ("\\p{Cn}\\P{C}", "\u{378}a", true),
+            ("(a*)*b", "aaab", true),
+            ("((a?)?)*", "", true),
+            ("日本.", "日本語", true),
+        ];
+
+        for (pattern, input, expected) in cases.iter() {
+            let regex = Regex::new(pattern).unwrap();
+            assert_eq!(regex.is_match(input), *expected, "{} {}", pattern, input);
+        }
+    }
+
+    #[test]
+    fn test_regex_search() {
+        let regex = Regex::new("b.?d").unwrap();
+        assert!(regex.search("abcde"));
+        assert!(regex.search("bd"));
+        assert!(!regex.search("b\nd"));
+        assert!(Regex::new("").unwrap().search("x"));
+
+        let regex = Regex::ecma("^a|b$").unwrap();
+        assert!(regex.search("ax"));
+        assert!(regex.search("xb"));
+        assert!(!regex.search("xa"));
+        assert!(!regex.search("bx"));
+        assert!(Regex::new("^a$").unwrap().search("x^a$"));
+    }
+
+    #[test]
+    fn test_regex_ecma() {
+        let cases = [
+            ("^\\d{3}$", "123", true),
+            ("^\\d{3}$", "12a", false),
+            ("^\\d$", "٣", false),
+            ("^\\w+$", "a_Z9", true),
+            ("^\\w+$", "a-b", false),
+            ("^a\\sb$", "a\u{3000}b", true),
+            ("^\\D\\W\\S$", "a-b", true),
+            ("^\\S$", " ", false),
+            ("^[\\d\\s-]+$", "12 3-4", true),
+            ("^[^\\D]$", "5", true),
+            ("^(?:ab|c)+$", "abcab", true),
+            ("^(?:ab|c)+$", "abb", false),
+        ];
+
+        for (pattern, input, expected) in cases.iter() {
+            let regex = Regex::ecma(pattern).unwrap();
+            assert_eq!(regex.search(input), *expected, "{} {}", pattern, input);
+        }
+
+        for pattern in ["\\d", "\\W", "\\s", "(?:a)"].iter() {
+            assert!(Regex::new(pattern).is_none(), "{}", pattern);
+        }
+        for pattern in ["(?=a)", "(?", "[a-\\d]"].iter() {
+            assert!(Regex::ecma(pattern).is_none(), "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn test_regex_linear() {
+        // Both take exponential time, or overflow the stack, with backtracking.
+        let input = format!("{}b", "a".repeat(100_000));
+        assert!(!Regex::new("(a|a)*").unwrap().is_match(&input));
+        assert!(Regex::new("(a|a)*").unwrap().search(&input));
+        assert!(!Regex::ecma("^(a+)+$").unwrap().search(&input));
+        assert!(Regex::new("a*b").unwrap().is_match(&input));
+        assert!(!Regex::new("(a*)*c").unwrap().search(&input));
+    }
+
+    #[test]
+    fn test_regex_new_invalid() {
+        let nested = format!("{}a{}", "(".repeat(65), ")".repeat(65));
+        for pattern in [
+            "(",
+            "a)",
+            "*a",
+            "a{3,2}",
+            "[b-a]",
+            "\\d",
+            "\\p{Xx}",
+            "\\p{Cs}",
+            "\\p{}",
+            "\\p{LL}",
+            "[a",
+            "a{",
+            "a{10001}",
+            "(a{100}){101}",
+            &nested,
+        ]
+        .iter()
+        {
+            assert!(Regex::new(pattern).is_none(), "{}", pattern);
+        }
+        assert!(Regex::new(&nested[1..nested.len() - 1]).is_some());
+    }
+}
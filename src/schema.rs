@@ -0,0 +1,903 @@
+/// JSON Schema (draft 2020-12) validation
+///
+/// {
+///   "$defs": { "port": { "type": "integer", "minimum": 1, "maximum": 65535 } },
+///   "type": "object",
+///   "properties": {
+///     "host": { "type": "string", "minLength": 1 },
+///     "ports": { "type": "array", "items": { "$ref": "#/$defs/port" } }
+///   },
+///   "required": ["host"]
+/// }
+///
+/// Supported keywords:
+///
+/// - type, enum, const
+/// - multipleOf, maximum, exclusiveMaximum, minimum, exclusiveMinimum
+/// - maxLength, minLength, pattern
+/// - prefixItems, items, maxItems, minItems, uniqueItems
+/// - properties, patternProperties, additionalProperties, required, maxProperties,
+///   minProperties
+/// - allOf, anyOf, oneOf, not
+/// - $ref to a JSON Pointer fragment of the same document, e.g. "#/$defs/port", which is
+///   percent-decoded
+///
+/// Other keywords are ignored. Numbers are compared exactly by their lexemes. Patterns are
+/// I-Regexp with the common extensions of ECMA-262 such as `^`, `$` and `\d`.
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::cmp;
+use crate::de::Value;
+use crate::number::Decimal;
+use crate::pointer;
+use crate::regex::Regex;
+
+/// An invalid schema, and where the invalid keyword is.
+#[derive(Eq, PartialEq, Debug)]
+pub struct SchemaError {
+    pub schema_path: String,
+}
+
+/// A keyword which an instance does not satisfy.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ValidationError {
+    /// JSON Pointer to the invalid value in the instance.
+    pub instance_path: String,
+    /// JSON Pointer to the failed keyword in the schema.
+    pub schema_path: String,
+    pub keyword: &'static str,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "\"{}\" does not satisfy \"{}\" at \"{}\"",
+            self.instance_path, self.keyword, self.schema_path
+        )
+    }
+}
+
+/// A compiled schema.
+#[derive(Debug)]
+pub struct Schema {
+    /// Subschemas, where the root is the first one. They refer to each other by index.
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug)]
+enum Node {
+    Bool(bool, String),
+    Keywords(Box<Keywords>),
+}
+
+/// State of a node while looking for cycles.
+#[derive(Clone, Copy)]
+enum Visit {
+    New,
+    Active,
+    Done,
+}
+
+#[derive(Default, Debug)]
+struct Keywords {
+    path: String,
+    reference: Option<usize>,
+    types: Option<Vec<String>>,
+    enumeration: Option<Vec<Value>>,
+    constant: Option<Value>,
+    multiple_of: Option<Decimal>,
+    maximum: Option<Decimal>,
+    exclusive_maximum: Option<Decimal>,
+    minimum: Option<Decimal>,
+    exclusive_minimum: Option<Decimal>,
+    max_length: Option<usize>,
+    min_length: Option<usize>,
+    pattern: Option<Regex>,
+    prefix_items: Vec<usize>,
+    items: Option<usize>,
+    max_items: Option<usize>,
+    min_items: Option<usize>,
+    unique_items: bool,
+    properties: Vec<(String, usize)>,
+    pattern_properties: Vec<(Regex, usize)>,
+    additional_properties: Option<usize>,
+    required: Vec<String>,
+    max_properties: Option<usize>,
+    min_properties: Option<usize>,
+    all_of: Vec<usize>,
+    any_of: Vec<usize>,
+    one_of: Vec<usize>,
+    not: Option<usize>,
+}
+
+impl Schema {
+    pub fn compile(schema: &Value) -> Result<Schema, SchemaError> {
+        let mut compiler = Compiler {
+            root: schema,
+            nodes: vec![],
+            indices: HashMap::new(),
+        };
+        compiler.compile("")?;
+
+        let schema = Schema {
+            nodes: compiler
+                .nodes
+                .into_iter()
+                .map(|node| node.expect("every reserved node is compiled"))
+                .collect(),
+        };
+        let mut states = vec![Visit::New; schema.nodes.len()];
+        for index in 0..schema.nodes.len() {
+            schema.check_cycle(index, &mut states)?;
+        }
+
+        Ok(schema)
+    }
+
+    /// Fails at a subschema which applies to the same instance as itself through `$ref` and
+    /// in-place applicators, such as `{"$ref": "#"}`, since validation would never end.
+    fn check_cycle(&self, index: usize, states: &mut [Visit]) -> Result<(), SchemaError> {
+        let keywords = match (&self.nodes[index], states[index]) {
+            (_, Visit::Done) | (Node::Bool(..), _) => return Ok(()),
+            (Node::Keywords(keywords), Visit::Active) => {
+                return Err(SchemaError {
+                    schema_path: keywords.path.clone(),
+                })
+            }
+            (Node::Keywords(keywords), Visit::New) => keywords,
+        };
+
+        states[index] = Visit::Active;
+        let in_place = keywords
+            .reference
+            .iter()
+            .chain(keywords.all_of.iter())
+            .chain(keywords.any_of.iter())
+            .chain(keywords.one_of.iter())
+            .chain(keywords.not.iter());
+        for next in in_place {
+            self.check_cycle(*next, states)?;
+        }
+        states[index] = Visit::Done;
+
+        Ok(())
+    }
+
+    /// Every keyword which `instance` fails.
+    pub fn validate(&self, instance: &Value) -> Result<(), Vec<ValidationError>> {
+        let mut errors = vec![];
+        self.check(0, instance, "", "false", &mut errors);
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn is_valid(&self, instance: &Value) -> bool {
+        self.validate(instance).is_ok()
+    }
+
+    fn valid(&self, index: usize, instance: &Value) -> bool {
+        let mut errors = vec![];
+        self.check(index, instance, "", "false", &mut errors);
+        errors.is_empty()
+    }
+
+    /// `applicator` is the keyword which applies the schema at `index`, which is reported if the
+    /// schema is `false`, or "false" for the root.
+    fn check(
+        &self,
+        index: usize,
+        instance: &Value,
+        instance_path: &str,
+        applicator: &'static str,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let keywords = match &self.nodes[index] {
+            Node::Bool(true, _) => return,
+            Node::Bool(false, path) => {
+                errors.push(ValidationError {
+                    instance_path: instance_path.to_string(),
+                    schema_path: path.to_string(),
+                    keyword: applicator,
+                });
+                return;
+            }
+            Node::Keywords(keywords) => keywords,
+        };
+
+        let mut fail = |keyword: &'static str| {
+            errors.push(ValidationError {
+                instance_path: instance_path.to_string(),
+                schema_path: format!("{}/{}", keywords.path, keyword),
+                keyword,
+            });
+        };
+
+        if let Some(types) = &keywords.types {
+            if !types.iter().any(|t| has_type(instance, t)) {
+                fail("type");
+            }
+        }
+        if let Some(values) = &keywords.enumeration {
+            if !values.iter().any(|v| cmp::equals(v, instance)) {
+                fail("enum");
+            }
+        }
+        if let Some(value) = &keywords.constant {
+            if !cmp::equals(value, instance) {
+                fail("const");
+            }
+        }
+
+        if let Value::Number(n) = instance {
+            if let Some(n) = Decimal::parse(n) {
+                if let Some(divisor) = &keywords.multiple_of {
+                    if !n.is_multiple_of(divisor) {
+                        fail("multipleOf");
+                    }
+                }
+                if keywords.maximum.as_ref().is_some_and(|max| n > *max) {
+                    fail("maximum");
+                }
+                if keywords
+                    .exclusive_maximum
+                    .as_ref()
+                    .is_some_and(|max| n >= *max)
+                {
+                    fail("exclusiveMaximum");
+                }
+                if keywords.minimum.as_ref().is_some_and(|min| n < *min) {
+                    fail("minimum");
+                }
+                if keywords
+                    .exclusive_minimum
+                    .as_ref()
+                    .is_some_and(|min| n <= *min)
+                {
+                    fail("exclusiveMinimum");
+                }
+            }
+        }
+
+        if let Value::String(s) = instance {
+            let len = s.chars().count();
+            if keywords.max_length.is_some_and(|max| len > max) {
+                fail("maxLength");
+            }
+            if keywords.min_length.is_some_and(|min| len < min) {
+                fail("minLength");
+            }
+            if let Some(regex) = &keywords.pattern {
+                if !regex.search(s) {
+                    fail("pattern");
+                }
+            }
+        }
+
+        if let Value::Array(array) = instance {
+            if keywords.max_items.is_some_and(|max| array.len() > max) {
+                fail("maxItems");
+            }
+            if keywords.min_items.is_some_and(|min| array.len() < min) {
+                fail("minItems");
+            }
+            if keywords.unique_items {
+                let duplicated = array
+                    .iter()
+                    .enumerate()
+                    .any(|(i, a)| array[i + 1..].iter().any(|b| cmp::equals(a, b)));
+                if duplicated {
+                    fail("uniqueItems");
+                }
+            }
+        }
+
+        if let Value::Object(object) = instance {
+            if keywords
+                .max_properties
+                .is_some_and(|max| object.len() > max)
+            {
+                fail("maxProperties");
+            }
+            if keywords
+                .min_properties
+                .is_some_and(|min| object.len() < min)
+            {
+                fail("minProperties");
+            }
+            if keywords.required.iter().any(|k| !object.contains_key(k)) {
+                fail("required");
+            }
+        }
+
+        let all_of = keywords.all_of.iter().all(|i| self.valid(*i, instance));
+        if !all_of {
+            fail("allOf");
+        }
+        let any_of = keywords.any_of.iter().any(|i| self.valid(*i, instance));
+        if !keywords.any_of.is_empty() && !any_of {
+            fail("anyOf");
+        }
+        let one_of = keywords
+            .one_of
+            .iter()
+            .filter(|i| self.valid(**i, instance))
+            .count();
+        if !keywords.one_of.is_empty() && one_of != 1 {
+            fail("oneOf");
+        }
+        if keywords.not.is_some_and(|i| self.valid(i, instance)) {
+            fail("not");
+        }
+
+        // Applicators which report the errors of their subschemas.
+        if let Some(index) = keywords.reference {
+            self.check(index, instance, instance_path, "$ref", errors);
+        }
+
+        if let Value::Array(array) = instance {
+            for (i, item) in array.iter().enumerate() {
+                let schema = match keywords.prefix_items.get(i) {
+                    Some(schema) => Some((*schema, "prefixItems")),
+                    None => keywords.items.map(|schema| (schema, "items")),
+                };
+                if let Some((schema, applicator)) = schema {
+                    let path = format!("{}/{}", instance_path, i);
+                    self.check(schema, item, &path, applicator, errors);
+                }
+            }
+        }
+
+        if let Value::Object(object) = instance {
+            let mut keys: Vec<&String> = object.keys().collect();
+            keys.sort();
+
+            for key in keys {
+                let value = &object[key];
+                let path = format!("{}/{}", instance_path, pointer::escape(key));
+                let mut matched = false;
+
+                for (name, schema) in keywords.properties.iter() {
+                    if name == key {
+                        matched = true;
+                        self.check(*schema, value, &path, "properties", errors);
+                    }
+                }
+                for (regex, schema) in keywords.pattern_properties.iter() {
+                    if regex.search(key) {
+                        matched = true;
+                        self.check(*schema, value, &path, "patternProperties", errors);
+                    }
+                }
+                if let (false, Some(schema)) = (matched, keywords.additional_properties) {
+                    self.check(schema, value, &path, "additionalProperties", errors);
+                }
+            }
+        }
+    }
+}
+
+fn has_type(instance: &Value, t: &str) -> bool {
+    match (t, instance) {
+        ("null", Value::Null)
+        | ("boolean", Value::Bool(_))
+        | ("object", Value::Object(_))
+        | ("array", Value::Array(_))
+        | ("number", Value::Number(_))
+        | ("string", Value::String(_)) => true,
+        ("integer", Value::Number(n)) => Decimal::parse(n).is_some_and(|n| n.is_integer()),
+        _ => false,
+    }
+}
+
+struct Compiler<'a> {
+    root: &'a Value,
+    /// `None` while being compiled, so that recursive references terminate.
+    nodes: Vec<Option<Node>>,
+    /// Indices of nodes by their JSON Pointers.
+    indices: HashMap<String, usize>,
+}
+
+impl Compiler<'_> {
+    fn compile(&mut self, path: &str) -> Result<usize, SchemaError> {
+        if let Some(index) = self.indices.get(path) {
+            return Ok(*index);
+        }
+
+        let schema = self.root.pointer(path).map_err(|_| SchemaError {
+            schema_path: path.to_string(),
+        })?;
+
+        let index = self.nodes.len();
+        self.nodes.push(None);
+        self.indices.insert(path.to_string(), index);
+
+        let node = match schema {
+            Value::Bool(b) => Node::Bool(*b, path.to_string()),
+            Value::Object(object) => Node::Keywords(Box::new(self.keywords(object, path)?)),
+            _ => {
+                return Err(SchemaError {
+                    schema_path: path.to_string(),
+                })
+            }
+        };
+        self.nodes[index] = Some(node);
+
+        Ok(index)
+    }
+
+    fn keywords(
+        &mut self,
+        object: &HashMap<String, Value>,
+        path: &str,
+    ) -> Result<Keywords, SchemaError> {
+        let mut keywords = Keywords {
+            path: path.to_string(),
+            ..Keywords::default()
+        };
+
+        let mut keys: Vec<&String> = object.keys().collect();
+        keys.sort();
+
+        for key in keys {
+            let value = &object[key];
+            let keyword_path = format!("{}/{}", path, pointer::escape(key));
+            let error = || SchemaError {
+                schema_path: keyword_path.clone(),
+            };
+
+            match key.as_str() {
+                "$ref" => {
+                    let fragment = match value {
+                        Value::String(s) if s.starts_with('#') => {
+                            percent_decode(&s[1..]).ok_or_else(error)?
+                        }
+                        _ => return Err(error()),
+                    };
+                    keywords.reference = Some(self.compile(&fragment).map_err(|_| error())?);
+                }
+                "type" => {
+                    let types = match value {
+                        Value::String(s) => vec![s.clone()],
+                        Value::Array(array) => array
+                            .iter()
+                            .map(|t| match t {
+                                Value::String(s) => Ok(s.clone()),
+                                _ => Err(error()),
+                            })
+                            .collect::<Result<_, _>>()?,
+                        _ => return Err(error()),
+                    };
+                    let known = ["null", "boolean", "object", "array", "number", "string"];
+                    if !types
+                        .iter()
+                        .all(|t| t == "integer" || known.contains(&t.as_str()))
+                    {
+                        return Err(error());
+                    }
+                    keywords.types = Some(types);
+                }
+                "enum" => match value {
+                    Value::Array(array) => keywords.enumeration = Some(array.clone()),
+                    _ => return Err(error()),
+                },
+                "const" => keywords.constant = Some(value.clone()),
+                "multipleOf" => match decimal(value) {
+                    Some(d) if d > Decimal::zero() => keywords.multiple_of = Some(d),
+                    _ => return Err(error()),
+                },
+                "maximum" => keywords.maximum = Some(decimal(value).ok_or_else(error)?),
+                "exclusiveMaximum" => {
+                    keywords.exclusive_maximum = Some(decimal(value).ok_or_else(error)?)
+                }
+                "minimum" => keywords.minimum = Some(decimal(value).ok_or_else(error)?),
+                "exclusiveMinimum" => {
+                    keywords.exclusive_minimum = Some(decimal(value).ok_or_else(error)?)
+                }
+                "maxLength" => keywords.max_length = Some(count(value).ok_or_else(error)?),
+                "minLength" => keywords.min_length = Some(count(value).ok_or_else(error)?),
+                "pattern" => keywords.pattern = Some(regex(value).ok_or_else(error)?),
+                "prefixItems" => keywords.prefix_items = self.schemas(value, &keyword_path)?,
+                "items" => keywords.items = Some(self.compile(&keyword_path)?),
+                "maxItems" => keywords.max_items = Some(count(value).ok_or_else(error)?),
+                "minItems" => keywords.min_items = Some(count(value).ok_or_else(error)?),
+                "uniqueItems" => match value {
+                    Value::Bool(b) => keywords.unique_items = *b,
+                    _ => return Err(error()),
+                },
+                "properties" | "patternProperties" => {
+                    let properties = match value {
+                        Value::Object(properties) => properties,
+                        _ => return Err(error()),
+                    };
+                    let mut names: Vec<&String> = properties.keys().collect();
+                    names.sort();
+
+                    for name in names {
+                        let schema =
+                            self.compile(&format!("{}/{}", keyword_path, pointer::escape(name)))?;
+                        if key == "properties" {
+                            keywords.properties.push((name.clone(), schema));
+                        } else {
+                            let regex = Regex::ecma(name).ok_or_else(error)?;
+                            keywords.pattern_properties.push((regex, schema));
+                        }
+                    }
+                }
+                "additionalProperties" => {
+                    keywords.additional_properties = Some(self.compile(&keyword_path)?)
+                }
+                "required" => {
+                    keywords.required = match value {
+                        Value::Array(array) => array
+                            .iter()
+                            .map(|name| match name {
+                                Value::String(s) => Ok(s.clone()),
+                                _ => Err(error()),
+                            })
+                            .collect::<Result<_, _>>()?,
+                        _ => return Err(error()),
+                    }
+                }
+                "maxProperties" => keywords.max_properties = Some(count(value).ok_or_else(error)?),
+                "minProperties" => keywords.min_properties = Some(count(value).ok_or_else(error)?),
+                "allOf" => keywords.all_of = self.schemas(value, &keyword_path)?,
+                "anyOf" => keywords.any_of = self.schemas(value, &keyword_path)?,
+                "oneOf" => keywords.one_of = self.schemas(value, &keyword_path)?,
+                "not" => keywords.not = Some(self.compile(&keyword_path)?),
+                _ => {}
+            }
+        }
+
+        Ok(keywords)
+    }
+
+    /// A non-empty array of schemas.
+    fn schemas(&mut self, value: &Value, path: &str) -> Result<Vec<usize>, SchemaError> {
+        match value {
+            Value::Array(array) if !array.is_empty() => (0..array.len())
+                .map(|i| self.compile(&format!("{}/{}", path, i)))
+                .collect(),
+            _ => Err(SchemaError {
+                schema_path: path.to_string(),
+            }),
+        }
+    }
+}
+
+/// Decodes `%XX` in a URI fragment (RFC 6901 section 6), or `None` if it is malformed or not
+/// UTF-8.
+fn percent_decode(fragment: &str) -> Option<String> {
+    let mut bytes = vec![];
+    let mut rest = fragment.as_bytes();
+
+    while let Some((&b, tail)) = rest.split_first() {
+        if b == b'%' {
+            let hex = tail
+                .get(..2)
+                .filter(|h| h.iter().all(u8::is_ascii_hexdigit))?;
+            bytes.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(b);
+            rest = tail;
+        }
+    }
+
+    String::from_utf8(bytes).ok()
+}
+
+fn decimal(value: &Value) -> Option<Decimal> {
+    match value {
+        Value::Number(n) => Decimal::parse(n),
+        _ => None,
+    }
+}
+
+/// A non-negative integer.
+fn count(value: &Value) -> Option<usize> {
+    let n = decimal(value)?;
+    if !n.is_integer() || n < Decimal::zero() {
+        return None;
+    }
+
+    match value {
+        Value::Number(lexeme) => lexeme
+            .parse::<f64>()
+            .ok()
+            .map(|f| f.min(usize::MAX as f64) as usize),
+        _ => None,
+    }
+}
+
+fn regex(value: &Value) -> Option<Regex> {
+    match value {
+        Value::String(s) => Regex::ecma(s),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::de::parse;
+
+    fn schema(input: &str) -> Schema {
+        Schema::compile(&parse(input)).unwrap()
+    }
+
+    /// (instance path, keyword) of each error.
+    fn errors(schema: &Schema, instance: &str) -> Vec<(String, &'static str)> {
+        match schema.validate(&parse(instance)) {
+            Ok(()) => vec![],
+            Err(errors) => errors
+                .into_iter()
+                .map(|e| (e.instance_path, e.keyword))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_schema_validate() {
+        let schema = schema(
+            r##"
+{
+  "$defs": {"port": {"type": "integer", "minimum": 1, "maximum": 65535}},
+  "type": "object",
+  "properties": {
+    "host": {"type": "string", "minLength": 1, "pattern": "^[a-z.]+$"},
+    "ports": {"type": "array", "items": {"$ref": "#/$defs/port"}, "uniqueItems": true},
+    "mode": {"enum": ["fast", "safe", 1.0]},
+    "version": {"const": 2}
+  },
+  "required": ["host"],
+  "additionalProperties": false
+}
+"##,
+        );
+
+        assert!(schema.is_valid(&parse(r#"{"host": "example.com"}"#)));
+        assert!(schema.is_valid(&parse(
+            r#"{"host": "a", "ports": [80, 443], "mode": 1, "version": 2.00}"#
+        )));
+
+        assert_eq!(
+            errors(&schema, r#"{"ports": [0, 8080.5, 70000, 0], "x": 1}"#),
+            vec![
+                ("".to_string(), "required"),
+                ("/ports".to_string(), "uniqueItems"),
+                ("/ports/0".to_string(), "minimum"),
+                ("/ports/1".to_string(), "type"),
+                ("/ports/2".to_string(), "maximum"),
+                ("/ports/3".to_string(), "minimum"),
+                ("/x".to_string(), "additionalProperties"),
+            ]
+        );
+        assert_eq!(
+            errors(&schema, r#"{"host": "", "mode": "slow", "version": 3}"#),
+            vec![
+                ("/host".to_string(), "minLength"),
+                ("/host".to_string(), "pattern"),
+                ("/mode".to_string(), "enum"),
+                ("/version".to_string(), "const"),
+            ]
+        );
+        assert_eq!(errors(&schema, "[]"), vec![("".to_string(), "type")]);
+
+        let error = &schema
+            .validate(&parse(r#"{"host": "a", "ports": [0]}"#))
+            .unwrap_err()[0];
+        assert_eq!(error.schema_path, "/$defs/port/minimum");
+        assert_eq!(
+            error.to_string(),
+            "\"/ports/0\" does not satisfy \"minimum\" at \"/$defs/port/minimum\""
+        );
+    }
+
+    #[test]
+    fn test_schema_numbers() {
+        let schema =
+            schema(r#"{"multipleOf": 0.01, "exclusiveMinimum": 0, "exclusiveMaximum": 100}"#);
+
+        assert!(schema.is_valid(&parse("19.99")));
+        assert!(schema.is_valid(&parse("99.990000")));
+        assert!(schema.is_valid(&parse("\"not a number\"")));
+        assert_eq!(
+            errors(&schema, "0.001"),
+            vec![("".to_string(), "multipleOf")]
+        );
+        assert_eq!(
+            errors(&schema, "0"),
+            vec![("".to_string(), "exclusiveMinimum")]
+        );
+        assert_eq!(
+            errors(&schema, "100.0"),
+            vec![("".to_string(), "exclusiveMaximum")]
+        );
+
+        let schema = self::schema(r#"{"type": ["integer", "null"]}"#);
+        assert!(schema.is_valid(&parse("1.0")));
+        assert!(schema.is_valid(&parse("null")));
+        assert!(!schema.is_valid(&parse("1.5")));
+    }
+
+    #[test]
+    fn test_schema_items() {
+        let schema = schema(
+            r#"{"prefixItems": [{"type": "string"}, {"type": "number"}], "items": false, "minItems": 1, "maxItems": 2}"#,
+        );
+
+        assert!(schema.is_valid(&parse(r#"["a"]"#)));
+        assert!(schema.is_valid(&parse(r#"["a", 1]"#)));
+        assert_eq!(
+            errors(&schema, r#"[1, "a", null]"#),
+            vec![
+                ("".to_string(), "maxItems"),
+                ("/0".to_string(), "type"),
+                ("/1".to_string(), "type"),
+                ("/2".to_string(), "items"),
+            ]
+        );
+        assert_eq!(errors(&schema, "[]"), vec![("".to_string(), "minItems")]);
+
+        // `false` is reported as the keyword which applies it.
+        let schema = self::schema(
+            r##"{"$defs": {"never": false}, "prefixItems": [false], "items": {"properties": {"a": false}, "patternProperties": {"^b": {"$ref": "#/$defs/never"}}}}"##,
+        );
+        assert_eq!(
+            errors(&schema, r#"[0, {"a": 1, "b": 2}]"#),
+            vec![
+                ("/0".to_string(), "prefixItems"),
+                ("/1/a".to_string(), "properties"),
+                ("/1/b".to_string(), "$ref"),
+            ]
+        );
+        assert_eq!(
+            errors(&self::schema("false"), "0"),
+            vec![("".to_string(), "false")]
+        );
+    }
+
+    #[test]
+    fn test_schema_combinators() {
+        let schema = schema(
+            r#"
+{
+  "anyOf": [{"type": "string"}, {"type": "number"}],
+  "oneOf": [{"type": "string"}, {"type": "number", "multipleOf": 2}, {"type": "number", "minimum": 0}],
+  "not": {"const": "forbidden"},
+  "allOf": [{"maxLength": 3}]
+}
+"#,
+        );
+
+        assert!(schema.is_valid(&parse("1")));
+        assert!(schema.is_valid(&parse("-4")));
+        assert!(schema.is_valid(&parse("\"abc\"")));
+        assert_eq!(errors(&schema, "2"), vec![("".to_string(), "oneOf")]);
+        assert_eq!(errors(&schema, "-1"), vec![("".to_string(), "oneOf")]);
+        assert_eq!(
+            errors(&schema, "null"),
+            vec![("".to_string(), "anyOf"), ("".to_string(), "oneOf")]
+        );
+        assert_eq!(
+            errors(&schema, "\"forbidden\""),
+            vec![("".to_string(), "allOf"), ("".to_string(), "not")]
+        );
+    }
+
+    #[test]
+    fn test_schema_ref_cycle() {
+        let cases = [
+            (r##"{"$ref": "#"}"##, ""),
+            (
+                r##"{"$defs": {"a": {"$ref": "#/$defs/b"}, "b": {"$ref": "#/$defs/a"}}, "$ref": "#/$defs/a"}"##,
+                "/$defs/a",
+            ),
+            (
+                r##"{"$defs": {"a": {"allOf": [{"not": {"$ref": "#/$defs/a"}}]}}, "$ref": "#/$defs/a"}"##,
+                "/$defs/a",
+            ),
+        ];
+        for (input, path) in cases.iter() {
+            assert_eq!(
+                Schema::compile(&parse(input)).unwrap_err().schema_path,
+                *path,
+                "{}",
+                input
+            );
+        }
+
+        // A cycle through an applicator to members or elements ends with the instance.
+        let schema = schema(r##"{"items": {"$ref": "#"}, "properties": {"a": {"$ref": "#"}}}"##);
+        assert!(schema.is_valid(&parse(r#"[[{"a": []}]]"#)));
+    }
+
+    #[test]
+    fn test_schema_ref_fragment() {
+        let schema = schema(
+            r##"{"$defs": {"a b": {"type": "string"}, "%": {"type": "null"}}, "anyOf": [{"$ref": "#/$defs/a%20b"}, {"$ref": "#/$defs/%25"}]}"##,
+        );
+        assert!(schema.is_valid(&parse("\"x\"")));
+        assert!(schema.is_valid(&parse("null")));
+        assert!(!schema.is_valid(&parse("1")));
+        for fragment in ["#/%2", "#/%zz", "#/%ff"].iter() {
+            let input = format!(r#"{{"$ref": "{}"}}"#, fragment);
+            assert_eq!(
+                Schema::compile(&parse(&input)).unwrap_err().schema_path,
+                "/$ref"
+            );
+        }
+    }
+
+    #[test]
+    fn test_schema_recursive_ref() {
+        let schema = schema(
+            r##"
+{
+  "$defs": {
+    "node": {
+      "type": "object",
+      "properties": {"value": {"type": "number"}, "children": {"type": "array", "items": {"$ref": "#/$defs/node"}}},
+      "patternProperties": {"^x-": {"type": "string"}},
+      "required": ["value"]
+    }
+  },
+  "$ref": "#/$defs/node"
+}
+"##,
+        );
+
+        assert!(schema.is_valid(&parse(
+            r#"{"value": 1, "children": [{"value": 2, "children": []}], "x-note": "ok"}"#
+        )));
+        assert_eq!(
+            errors(
+                &schema,
+                r#"{"value": 1, "children": [{"children": [{"value": "2"}]}], "x-note": 1}"#
+            ),
+            vec![
+                ("/children/0".to_string(), "required"),
+                ("/children/0/children/0/value".to_string(), "type"),
+                ("/x-note".to_string(), "type"),
+            ]
+        );
+
+        let schema =
+            self::schema(r##"{"properties": {"next": {"$ref": "#"}}, "maxProperties": 1}"##);
+        assert!(schema.is_valid(&parse(r#"{"next": {"next": {}}}"#)));
+        assert!(!schema.is_valid(&parse(r#"{"next": {"next": {"a": 1, "b": 2}}}"#)));
+    }
+
+    #[test]
+    fn test_schema_compile_error() {
+        let cases = [
+            (r#"{"type": "float"}"#, "/type"),
+            (r#"{"minimum": "1"}"#, "/minimum"),
+            (r#"{"multipleOf": 0}"#, "/multipleOf"),
+            (r#"{"minLength": -1}"#, "/minLength"),
+            (r#"{"pattern": "("}"#, "/pattern"),
+            (r#"{"allOf": []}"#, "/allOf"),
+            (r##"{"$ref": "#/$defs/missing"}"##, "/$ref"),
+            (r#"{"$ref": "other.json"}"#, "/$ref"),
+            (r#"{"properties": {"a": 1}}"#, "/properties/a"),
+            ("1", ""),
+        ];
+
+        for (schema, path) in cases.iter() {
+            assert_eq!(
+                Schema::compile(&parse(schema)).unwrap_err(),
+                SchemaError {
+                    schema_path: path.to_string()
+                },
+                "{}",
+                schema
+            );
+        }
+    }
+}
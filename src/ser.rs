@@ -0,0 +1,265 @@
+/// JSON serializer
+///
+/// Object members are sorted by key, so the same `Value` is always serialized the same way.
+/// Numbers are written as their lexemes. `NaN`, `Infinity` and `-Infinity`, which are not JSON
+/// but parsed with `ParserOptions::non_finite_numbers`, fail unless `SerializerOptions` allows
+/// them, as `JsonWriter` does.
+///
+/// {"a":[1,true],"b":null}
+///
+/// {
+///   "a": [
+///     1,
+///     true
+///   ],
+///   "b": null
+/// }
+use std::fmt;
+
+use crate::de::Value;
+use crate::number::non_finite;
+
+/// How to write the number lexemes `NaN`, `Infinity` and `-Infinity`.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum NonFiniteNumbers {
+    /// As they are, which Python's `json` and JSON5 read.
+    Literal,
+    /// As `null`, as JavaScript's `JSON.stringify` does.
+    Null,
+    /// Fails with `SerializeError::NonFiniteNumber`.
+    Error,
+}
+
+#[derive(Clone, Debug)]
+pub struct SerializerOptions {
+    indent: Option<String>,
+    non_finite_numbers: NonFiniteNumbers,
+}
+
+impl Default for SerializerOptions {
+    fn default() -> SerializerOptions {
+        SerializerOptions {
+            indent: None,
+            non_finite_numbers: NonFiniteNumbers::Error,
+        }
+    }
+}
+
+impl SerializerOptions {
+    /// Without any whitespaces, and failing at non-finite numbers.
+    pub fn new() -> SerializerOptions {
+        SerializerOptions::default()
+    }
+
+    /// A line for each element or member, indented with `indent` per level.
+    pub fn indent(mut self, indent: &str) -> SerializerOptions {
+        self.indent = Some(indent.to_string());
+        self
+    }
+
+    pub fn non_finite_numbers(mut self, policy: NonFiniteNumbers) -> SerializerOptions {
+        self.non_finite_numbers = policy;
+        self
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum SerializeError {
+    NonFiniteNumber(String),
+}
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SerializeError::NonFiniteNumber(n) => write!(f, "{} is not a JSON number", n),
+        }
+    }
+}
+
+impl std::error::Error for SerializeError {}
+
+/// Serializes into JSON without any whitespaces.
+pub fn to_string(value: &Value) -> Result<String, SerializeError> {
+    to_string_with(value, &SerializerOptions::new())
+}
+
+/// Serializes into JSON with a line for each element or member, indented with `indent` per level.
+pub fn to_string_pretty(value: &Value, indent: &str) -> Result<String, SerializeError> {
+    to_string_with(value, &SerializerOptions::new().indent(indent))
+}
+
+pub fn to_string_with(
+    value: &Value,
+    options: &SerializerOptions,
+) -> Result<String, SerializeError> {
+    let mut out = String::new();
+    write(&mut out, value, options, 0)?;
+    Ok(out)
+}
+
+/// Same as `to_string`, except that non-finite numbers are written as they are, since `Display`
+/// can not fail with its own error.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let options = SerializerOptions::new().non_finite_numbers(NonFiniteNumbers::Literal);
+        match to_string_with(self, &options) {
+            Ok(s) => f.write_str(&s),
+            Err(_) => unreachable!("non-finite numbers are written as they are"),
+        }
+    }
+}
+
+fn write(
+    out: &mut String,
+    value: &Value,
+    options: &SerializerOptions,
+    depth: usize,
+) -> Result<(), SerializeError> {
+    let indent = options.indent.as_deref();
+
+    match value {
+        Value::Object(object) => {
+            let mut members: Vec<_> = object.iter().collect();
+            members.sort_by(|a, b| a.0.cmp(b.0));
+
+            out.push('{');
+            for (i, (key, value)) in members.iter().enumerate() {
+                separate(out, i, indent, depth);
+                out.push_str(&quote(key));
+                out.push(':');
+                if indent.is_some() {
+                    out.push(' ');
+                }
+                write(out, value, options, depth + 1)?;
+            }
+            close(out, members.is_empty(), indent, depth);
+            out.push('}');
+        }
+        Value::Array(array) => {
+            out.push('[');
+            for (i, value) in array.iter().enumerate() {
+                separate(out, i, indent, depth);
+                write(out, value, options, depth + 1)?;
+            }
+            close(out, array.is_empty(), indent, depth);
+            out.push(']');
+        }
+        Value::String(s) => out.push_str(&quote(s)),
+        Value::Number(n) => match options.non_finite_numbers {
+            NonFiniteNumbers::Null if non_finite(n).is_some() => out.push_str("null"),
+            NonFiniteNumbers::Error if non_finite(n).is_some() => {
+                return Err(SerializeError::NonFiniteNumber(n.clone()))
+            }
+            _ => out.push_str(n),
+        },
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Null => out.push_str("null"),
+    }
+
+    Ok(())
+}
+
+/// Writes what comes before the `i`th element or member.
+fn separate(out: &mut String, i: usize, indent: Option<&str>, depth: usize) {
+    if i > 0 {
+        out.push(',');
+    }
+    if let Some(indent) = indent {
+        out.push('\n');
+        out.push_str(&indent.repeat(depth + 1));
+    }
+}
+
+/// Writes what comes before the closing bracket of a container.
+fn close(out: &mut String, empty: bool, indent: Option<&str>, depth: usize) {
+    if let (Some(indent), false) = (indent, empty) {
+        out.push('\n');
+        out.push_str(&indent.repeat(depth));
+    }
+}
+
+/// A JSON string literal of `s`.
+pub(crate) fn quote(s: &str) -> String {
+    let mut quoted = "\"".to_string();
+
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '\x08' => quoted.push_str("\\b"),
+            '\x0C' => quoted.push_str("\\f"),
+            c if c < ' ' => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::de::{parse, Deserializer};
+    use crate::options::ParserOptions;
+
+    #[test]
+    fn test_to_string() {
+        let value = parse(r#"{"b": [1, 2.50, {}], "a": "x\"\n\t", "c": {"d": [], "e": null}}"#);
+
+        let json = to_string(&value).unwrap();
+        assert_eq!(
+            json,
+            r#"{"a":"x\"\n\t","b":[1,2.50,{}],"c":{"d":[],"e":null}}"#
+        );
+        assert_eq!(value.to_string(), json);
+        assert_eq!(parse(&json), value);
+        assert_eq!(quote("\u{1}\u{7f}"), "\"\\u0001\u{7f}\"");
+    }
+
+    #[test]
+    fn test_to_string_pretty() {
+        let value = parse(r#"{"b": [true, {"c": false}], "a": {}}"#);
+
+        assert_eq!(
+            to_string_pretty(&value, "  ").unwrap(),
+            "{\n  \"a\": {},\n  \"b\": [\n    true,\n    {\n      \"c\": false\n    }\n  ]\n}"
+        );
+        assert_eq!(to_string_pretty(&parse("[]"), "\t").unwrap(), "[]");
+        assert_eq!(to_string_pretty(&parse("1"), "\t").unwrap(), "1");
+    }
+
+    #[test]
+    fn test_to_string_with() {
+        let options = ParserOptions::new().non_finite_numbers(true);
+        let value = Deserializer::new_with_options(r#"{"a": [NaN, -Infinity, 1.5]}"#, options)
+            .parse()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            to_string(&value),
+            Err(SerializeError::NonFiniteNumber("NaN".to_string()))
+        );
+        assert_eq!(value.to_string(), r#"{"a":[NaN,-Infinity,1.5]}"#);
+        assert_eq!(
+            to_string_with(
+                &value,
+                &SerializerOptions::new()
+                    .indent(" ")
+                    .non_finite_numbers(NonFiniteNumbers::Literal)
+            ),
+            Ok("{\n \"a\": [\n  NaN,\n  -Infinity,\n  1.5\n ]\n}".to_string())
+        );
+        assert_eq!(
+            to_string_with(
+                &value,
+                &SerializerOptions::new().non_finite_numbers(NonFiniteNumbers::Null)
+            ),
+            Ok(r#"{"a":[null,null,1.5]}"#.to_string())
+        );
+    }
+}
@@ -3,10 +3,13 @@
 ///   "name": "jjsonsonpapaparser",
 ///   "desc": "toy json parser",
 /// }
+use std::fmt;
 use std::str;
 use std::string;
 
-#[derive(Eq, PartialEq, Debug)]
+use crate::options::ParserOptions;
+
+#[derive(Clone, Eq, PartialEq, Debug)]
 pub enum Token {
     LeftBracket,
     RightBracket,
@@ -24,7 +27,7 @@ pub enum Token {
 }
 
 impl Token {
-    fn to_char(self) -> Option<char> {
+    fn to_char(&self) -> Option<char> {
         match self {
             Token::LeftBracket => Some('['),
             Token::RightBracket => Some(']'),
@@ -37,29 +40,186 @@ impl Token {
     }
 }
 
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Clone, Eq, PartialEq, Debug)]
 pub enum Error {
-    SomethingError,
-    EOF,
+    Eof,
     InvalidEscapeChar,
     InvalidString,
     InvalidNumber,
     InvalidToken,
+    InvalidComment,
+    InputTooLong,
+    StringTooLong,
+    NumberTooLong,
+    TooManyMembers,
+    TooManyNodes,
+    TooDeep,
+    DuplicateKey(string::String),
+    ScalarRoot,
+    InvalidUtf8,
+    /// A string which is not closed before the end of the input, or before a line break if
+    /// strict, with the line where it starts.
+    UnterminatedString(usize),
+    SingleQuotedString,
+    UnquotedKey(string::String),
+    /// A member without ':' after its key.
+    ExpectedColon(string::String),
+    /// Two elements or members next to each other, with the closing bracket of the container.
+    MissingComma(char),
+    /// A comma right before the closing bracket, which is not allowed unless
+    /// `ParserOptions::trailing_commas`.
+    TrailingComma(char),
+    /// The end of the input in an object or array, with the line where it is opened.
+    UnclosedObject(usize),
+    UnclosedArray(usize),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            Error::Eof => "unexpected end of input",
+            Error::InvalidEscapeChar => "invalid escape character",
+            Error::InvalidString => "invalid string",
+            Error::InvalidNumber => "invalid number",
+            Error::InvalidToken => "unexpected token",
+            Error::InvalidComment => "invalid comment",
+            Error::InputTooLong => "input is too long",
+            Error::StringTooLong => "string is too long",
+            Error::NumberTooLong => "number is too long",
+            Error::TooManyMembers => "too many elements or members",
+            Error::TooManyNodes => "too many values",
+            Error::TooDeep => "nesting is too deep",
+            Error::DuplicateKey(key) => return write!(f, "duplicate key {:?}", key),
+            Error::ScalarRoot => "root is neither an array nor an object",
+            Error::InvalidUtf8 => "invalid UTF-8",
+            Error::UnterminatedString(line) => {
+                return write!(f, "unterminated string starting at line {}", line)
+            }
+            Error::SingleQuotedString => "single-quoted string",
+            Error::UnquotedKey(key) => return write!(f, "unquoted key `{}`", key),
+            Error::ExpectedColon(key) => return write!(f, "expected ':' after key {:?}", key),
+            Error::MissingComma('}') => "missing ',' between members",
+            Error::MissingComma(_) => "missing ',' between elements",
+            Error::TrailingComma(end) => return write!(f, "trailing comma before '{}'", end),
+            Error::UnclosedObject(line) => {
+                return write!(
+                    f,
+                    "unexpected end of input in object opened at line {}",
+                    line
+                )
+            }
+            Error::UnclosedArray(line) => {
+                return write!(
+                    f,
+                    "unexpected end of input in array opened at line {}",
+                    line
+                )
+            }
+        };
+
+        f.write_str(message)
+    }
+}
+
+impl Error {
+    /// How to fix the input, if it is likely enough.
+    pub fn hint(&self) -> Option<string::String> {
+        let hint = match self {
+            Error::UnterminatedString(_) => {
+                "add the closing '\"', and write line breaks in strings as \\n".to_string()
+            }
+            Error::SingleQuotedString => "use double quotes, as \"...\"".to_string(),
+            Error::UnquotedKey(key) => format!("quote the key, as {:?}", key),
+            Error::ExpectedColon(key) => format!("write the member as {:?}: value", key),
+            Error::MissingComma(_) => "add ',' before this".to_string(),
+            Error::TrailingComma(end) => format!("remove the last ',' before '{}'", end),
+            Error::UnclosedObject(_) => "add the closing '}'".to_string(),
+            Error::UnclosedArray(_) => "add the closing ']'".to_string(),
+            _ => return None,
+        };
+
+        Some(hint)
+    }
 }
 
+impl std::error::Error for Error {}
+
 /// Token parser
 pub struct Tokenizer<'a> {
+    input: &'a str,
     chars: str::CharIndices<'a>,
+    comments: bool,
+    non_finite: bool,
+    strict: bool,
 }
 
-impl Tokenizer<'_> {
-    pub fn new(input: &str) -> Tokenizer {
+impl<'a> Tokenizer<'a> {
+    pub fn new(input: &'a str) -> Tokenizer<'a> {
         Tokenizer {
+            input,
             chars: input.char_indices(),
+            comments: false,
+            non_finite: false,
+            strict: false,
+        }
+    }
+
+    /// Same as `new`, but with the dialect of `options`.
+    pub fn with_options(input: &'a str, options: &ParserOptions) -> Tokenizer<'a> {
+        Tokenizer {
+            comments: options.comments,
+            non_finite: options.non_finite_numbers,
+            strict: options.strict,
+            ..Tokenizer::new(input)
+        }
+    }
+
+    /// Same as `new`, but `//` and `/* */` comments are treated as trivia (JSONC).
+    pub fn with_comments(input: &'a str) -> Tokenizer<'a> {
+        Tokenizer {
+            comments: true,
+            ..Tokenizer::new(input)
         }
     }
 
+    /// Byte offset of the next character in the input.
+    pub fn offset(&self) -> usize {
+        self.chars.offset()
+    }
+
+    /// 1-based line and column of a byte offset. Columns count characters.
+    pub fn position_at(&self, offset: usize) -> (usize, usize) {
+        let consumed = self.slice(0, offset);
+
+        let line = consumed.matches('\n').count() + 1;
+        let column = match consumed.rfind('\n') {
+            Some(i) => consumed[i + 1..].chars().count() + 1,
+            None => consumed.chars().count() + 1,
+        };
+
+        (line, column)
+    }
+
+    /// Whether the whole input has been consumed.
+    pub fn is_end(&self) -> bool {
+        self.chars.clone().next().is_none()
+    }
+
+    /// The next character without eating it.
+    pub fn peek_char(&self) -> Option<char> {
+        self.chars.clone().next().map(|(_, c)| c)
+    }
+
+    /// Input slice between two byte offsets.
+    pub fn slice(&self, start: usize, end: usize) -> &'a str {
+        &self.input[start..end]
+    }
+
     pub fn next(&mut self) -> Result<Option<Token>, Error> {
+        if let Some(literal) = self.non_finite_number() {
+            return Ok(Some(Token::Number(literal.to_string())));
+        }
+
         match self.peek() {
             Some((_, '{')) => {
                 self.eat_one();
@@ -86,11 +246,32 @@ impl Tokenizer<'_> {
                 Ok(Some(Token::Colon))
             }
             Some((_, '"')) => self.string(),
-            Some((_, c)) if c.is_digit(10) || c == '-' => self.number(),
+            Some((_, c)) if c.is_ascii_digit() || c == '-' => self.number(),
             Some((_, c)) if c == 't' || c == 'f' => self.boolean(),
             Some((_, 'n')) => self.null(),
+            Some((_, '\'')) => Err(Error::SingleQuotedString),
             Some(_) => Err(Error::InvalidToken),
-            None => Err(Error::EOF),
+            None => Err(Error::Eof),
+        }
+    }
+
+    /// Same as `next`, but strings and numbers are only checked without being built, and come
+    /// empty. So nothing is allocated.
+    pub fn skip(&mut self) -> Result<Option<Token>, Error> {
+        if self.non_finite_number().is_some() {
+            return Ok(Some(Token::Number(string::String::new())));
+        }
+
+        match self.peek() {
+            Some((_, '"')) => {
+                self.skip_string()?;
+                Ok(Some(Token::String(string::String::new())))
+            }
+            Some((_, c)) if c.is_ascii_digit() || c == '-' => {
+                self.skip_number()?;
+                Ok(Some(Token::Number(string::String::new())))
+            }
+            _ => self.next(),
         }
     }
 
@@ -110,10 +291,7 @@ impl Tokenizer<'_> {
     }
 
     fn eat_one(&mut self) -> bool {
-        match self.one() {
-            Some(_) => true,
-            _ => false,
-        }
+        self.one().is_some()
     }
 
     fn eatc(&mut self, c: char) -> bool {
@@ -162,8 +340,106 @@ impl Tokenizer<'_> {
         true
     }
 
+    /// The input matching `[0-9A-Za-z_$]*` from the current position, without eating it. Such
+    /// as a bare word which is meant to be a string.
+    pub fn peek_word(&self) -> &'a str {
+        let rest = &self.input[self.offset()..];
+        let end = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '$'))
+            .unwrap_or(rest.len());
+        &rest[..end]
+    }
+
+    /// Eats the input up to the next `,`, `]` or `}` which is not nested in brackets, to go on
+    /// after an error. A string is eaten as a whole, or up to the end of its line if unterminated.
+    pub fn resync(&mut self) {
+        let mut depth = 0;
+
+        while let Some((_, c)) = self.peek() {
+            match c {
+                ',' | ']' | '}' if depth == 0 => return,
+                '[' | '{' => depth += 1,
+                ']' | '}' => depth -= 1,
+                '"' => {
+                    self.one();
+                    while let Some((_, c)) = self.peek() {
+                        match c {
+                            '\n' => break,
+                            '"' => {
+                                self.one();
+                                break;
+                            }
+                            '\\' => {
+                                self.one();
+                            }
+                            _ => {}
+                        }
+                        self.one();
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+
+            self.one();
+        }
+    }
+
+    /// Eats whitespaces, and also comments when they are enabled, then returns the eaten slice
+    /// as is.
+    pub fn trivia(&mut self) -> Result<&'a str, Error> {
+        let start = self.offset();
+
+        loop {
+            if self.eat_whitespaces() {
+                continue;
+            }
+
+            if self.comments && self.eat_comment()? {
+                continue;
+            }
+
+            break;
+        }
+
+        Ok(self.slice(start, self.offset()))
+    }
+
+    /// - // line comment
+    /// - /* block comment */
+    fn eat_comment(&mut self) -> Result<bool, Error> {
+        if self.eats("//") {
+            while let Some((_, c)) = self.peek() {
+                if c == '\n' {
+                    break;
+                }
+                self.one();
+            }
+
+            return Ok(true);
+        }
+
+        if self.eats("/*") {
+            loop {
+                if self.eats("*/") {
+                    return Ok(true);
+                }
+
+                if !self.eat_one() {
+                    return Err(Error::InvalidComment);
+                }
+            }
+        }
+
+        match self.peek() {
+            Some((_, '/')) => Err(Error::InvalidComment),
+            _ => Ok(false),
+        }
+    }
+
     /// Assumes that head quotation mark(") have been already eaten.
     fn string(&mut self) -> Result<Option<Token>, Error> {
+        let start = self.offset();
         let mut val = "".to_string();
 
         match self.peek() {
@@ -208,7 +484,10 @@ impl Tokenizer<'_> {
                             self.one();
                             val.push('\t');
                         }
-                        // TODO: implement `u`
+                        Some((_, 'u')) => {
+                            self.one();
+                            val.push(self.unicode_escape()?);
+                        }
                         _ => return Err(Error::InvalidEscapeChar),
                     }
                 }
@@ -216,15 +495,150 @@ impl Tokenizer<'_> {
                     self.one();
                     return Ok(Some(Token::String(val)));
                 }
+                Some((_, '\n')) if self.strict => return Err(self.unterminated(start)),
+                Some((_, c)) if self.strict && c < ' ' => return Err(Error::InvalidString),
                 Some((_, c)) => {
                     self.one();
                     val.push(c);
                 }
-                None => return Err(Error::EOF),
+                None => return Err(self.unterminated(start)),
             }
         }
     }
 
+    /// Same as `string`, but only checks it.
+    fn skip_string(&mut self) -> Result<(), Error> {
+        let start = self.offset();
+        if !self.eatc('"') {
+            return Err(Error::InvalidString);
+        }
+
+        loop {
+            match self.peek() {
+                Some((_, '\\')) => {
+                    self.one();
+                    match self.peek() {
+                        Some((_, '"')) | Some((_, '\\')) | Some((_, '/')) | Some((_, 'b'))
+                        | Some((_, 'f')) | Some((_, 'n')) | Some((_, 'r')) | Some((_, 't')) => {
+                            self.one();
+                        }
+                        Some((_, 'u')) => {
+                            self.one();
+                            self.unicode_escape()?;
+                        }
+                        _ => return Err(Error::InvalidEscapeChar),
+                    }
+                }
+                Some((_, '"')) => {
+                    self.one();
+                    return Ok(());
+                }
+                Some((_, '\n')) if self.strict => return Err(self.unterminated(start)),
+                Some((_, c)) if self.strict && c < ' ' => return Err(Error::InvalidString),
+                Some(_) => {
+                    self.one();
+                }
+                None => return Err(self.unterminated(start)),
+            }
+        }
+    }
+
+    /// `Error::UnterminatedString` for the string starting at `start`.
+    fn unterminated(&self, start: usize) -> Error {
+        Error::UnterminatedString(self.input[..start].matches('\n').count() + 1)
+    }
+
+    /// Assumes that "\u" has been already eaten. A surrogate pair is combined into one
+    /// character. Lone surrogates are replaced with U+FFFD unless strict.
+    fn unicode_escape(&mut self) -> Result<char, Error> {
+        let unit = self.hex4()?;
+
+        if let 0xD800..=0xDBFF = unit {
+            let saved = self.chars.clone();
+            if self.eats("\\u") {
+                if let low @ 0xDC00..=0xDFFF = self.hex4()? {
+                    let c = 0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00);
+                    return Ok(std::char::from_u32(c).unwrap());
+                }
+            }
+            self.chars = saved;
+        }
+
+        match std::char::from_u32(unit) {
+            Some(c) => Ok(c),
+            None if self.strict => Err(Error::InvalidEscapeChar),
+            None => Ok(std::char::REPLACEMENT_CHARACTER),
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, Error> {
+        let mut unit = 0;
+
+        for _ in 0..4 {
+            match self.peek().and_then(|(_, c)| c.to_digit(16)) {
+                Some(digit) => {
+                    self.one();
+                    unit = unit * 16 + digit;
+                }
+                None => return Err(Error::InvalidEscapeChar),
+            }
+        }
+
+        Ok(unit)
+    }
+
+    /// Eats `NaN`, `Infinity` or `-Infinity` if they are enabled.
+    fn non_finite_number(&mut self) -> Option<&'static str> {
+        if !self.non_finite {
+            return None;
+        }
+
+        ["NaN", "Infinity", "-Infinity"]
+            .iter()
+            .copied()
+            .find(|literal| self.eats(literal))
+    }
+
+    /// Same as `number`, but only checks it.
+    fn skip_number(&mut self) -> Result<(), Error> {
+        // Integer
+        self.eatc('-');
+        if !self.eatc('0') {
+            self.skip_digits()?;
+        }
+
+        // Fraction
+        if self.eatc('.') {
+            self.skip_digits()?;
+        }
+
+        // Exponent
+        if !(self.eatc('e') || self.eatc('E')) {
+            return Ok(());
+        }
+        if !self.eatc('-') {
+            self.eatc('+');
+        }
+        self.skip_digits()
+    }
+
+    /// Eats one or more digits.
+    fn skip_digits(&mut self) -> Result<(), Error> {
+        match self.peek() {
+            Some((_, c)) if c.is_ascii_digit() => {}
+            _ => return Err(Error::InvalidNumber),
+        }
+
+        while let Some((_, c)) = self.peek() {
+            if !c.is_ascii_digit() {
+                break;
+            }
+            self.eat_one();
+        }
+
+        Ok(())
+    }
+
     /// - 100
     /// - 0
     /// - 0.001e-10
@@ -233,21 +647,18 @@ impl Tokenizer<'_> {
 
         // Integer
         match self.integer() {
-            Ok(Some(Token::Integer(n))) => val.push_str(n.as_str()),
+            Ok(Some(Token::Integer(n))) if n != "-" => val.push_str(n.as_str()),
             _ => return Err(Error::InvalidNumber),
         }
 
         // Fraction
-        match self.peek() {
-            Some((_, '.')) => {
-                self.one();
-                val.push('.');
+        if self.eatc('.') {
+            val.push('.');
+
+            match self.fraction() {
+                Ok(Some(Token::Fraction(n))) => val.push_str(n.as_str()),
+                _ => return Err(Error::InvalidNumber),
             }
-            _ => return Ok(Some(Token::Number(val))),
-        }
-        match self.fraction() {
-            Ok(Some(Token::Fraction(n))) => val.push_str(n.as_str()),
-            _ => return Err(Error::InvalidNumber),
         }
 
         // Exponent
@@ -267,7 +678,7 @@ impl Tokenizer<'_> {
             _ => return Err(Error::InvalidNumber),
         }
 
-        return Ok(Some(Token::Number(val)));
+        Ok(Some(Token::Number(val)))
     }
 
     fn integer(&mut self) -> Result<Option<Token>, Error> {
@@ -289,7 +700,7 @@ impl Tokenizer<'_> {
 
         loop {
             match self.peek() {
-                Some((_, c)) if c.is_digit(10) => {
+                Some((_, c)) if c.is_ascii_digit() => {
                     self.eat_one();
                     val.push(c);
                 }
@@ -301,29 +712,24 @@ impl Tokenizer<'_> {
     fn fraction(&mut self) -> Result<Option<Token>, Error> {
         let mut val = "".to_string();
 
-        if let Some((_, c)) = self.peek() {
-            if c.is_digit(10) {
+        match self.peek() {
+            Some((_, c)) if c.is_ascii_digit() => {
                 self.one();
                 val.push(c);
-            } else {
-                return Err(Error::InvalidNumber);
             }
+            _ => return Err(Error::InvalidNumber),
         }
 
-        loop {
-            if let Some((_, c)) = self.peek() {
-                if c.is_digit(10) {
-                    self.eat_one();
-                    val.push(c);
-                } else {
-                    break;
-                }
+        while let Some((_, c)) = self.peek() {
+            if c.is_ascii_digit() {
+                self.eat_one();
+                val.push(c);
             } else {
                 break;
             }
         }
 
-        return Ok(Some(Token::Fraction(val)));
+        Ok(Some(Token::Fraction(val)))
     }
 
     fn exponent(&mut self) -> Result<Option<Token>, Error> {
@@ -339,9 +745,9 @@ impl Tokenizer<'_> {
         match self.fraction() {
             Ok(Some(Token::Fraction(f))) => {
                 val.push_str(f.as_str());
-                return Ok(Some(Token::Exponent(val)));
+                Ok(Some(Token::Exponent(val)))
             }
-            _ => return Err(Error::InvalidNumber),
+            _ => Err(Error::InvalidNumber),
         }
     }
 
@@ -411,15 +817,13 @@ mod tests {
         tokenizer.eat_whitespaces();
         assert_eq!(tokenizer.next(), Ok(Some(Token::RightBrace)));
         tokenizer.eat_whitespaces();
-        assert_eq!(tokenizer.next(), Err(Error::EOF));
+        assert_eq!(tokenizer.next(), Err(Error::Eof));
     }
 
     #[test]
     fn test_tokenizer_peek() {
         let input = "abcd";
-        let mut tokenizer = Tokenizer {
-            chars: input.char_indices(),
-        };
+        let mut tokenizer = Tokenizer::new(input);
 
         assert_eq!(tokenizer.peek(), Some((0, 'a')));
         assert_eq!(tokenizer.peek(), Some((0, 'a')));
@@ -429,9 +833,7 @@ mod tests {
     #[test]
     fn test_tokenizer_one() {
         let input = "abc";
-        let mut tokenizer = Tokenizer {
-            chars: input.char_indices(),
-        };
+        let mut tokenizer = Tokenizer::new(input);
 
         assert_eq!(tokenizer.one(), Some((0, 'a')));
         assert_eq!(tokenizer.one(), Some((1, 'b')));
@@ -442,9 +844,7 @@ mod tests {
     #[test]
     fn test_tokenizer_eat_one() {
         let input = "abc";
-        let mut tokenizer = Tokenizer {
-            chars: input.char_indices(),
-        };
+        let mut tokenizer = Tokenizer::new(input);
 
         assert!(tokenizer.eat_one());
         assert!(tokenizer.eat_one());
@@ -455,9 +855,7 @@ mod tests {
     #[test]
     fn test_tokenizer_eatc() {
         let input = "abc";
-        let mut tokenizer = Tokenizer {
-            chars: input.char_indices(),
-        };
+        let mut tokenizer = Tokenizer::new(input);
 
         assert!(tokenizer.eatc('a'));
         assert!(tokenizer.eatc('b'));
@@ -472,7 +870,7 @@ mod tests {
         let input = "true";
         let mut tokenizer = Tokenizer::new(input);
         assert!(tokenizer.eats("true"));
-        assert_eq!(tokenizer.next(), Err(Error::EOF));
+        assert_eq!(tokenizer.next(), Err(Error::Eof));
 
         let input = "truehoge";
         let mut tokenizer = Tokenizer::new(input);
@@ -489,9 +887,7 @@ mod tests {
     #[test]
     fn test_tokenizer_string() {
         let input = "\"abcde  f \"";
-        let mut tokenizer = Tokenizer {
-            chars: input.char_indices(),
-        };
+        let mut tokenizer = Tokenizer::new(input);
 
         assert_eq!(
             tokenizer.string(),
@@ -502,9 +898,7 @@ mod tests {
     #[test]
     fn test_tokenizer_number() {
         let input = "100";
-        let mut tokenizer = Tokenizer {
-            chars: input.char_indices(),
-        };
+        let mut tokenizer = Tokenizer::new(input);
 
         assert_eq!(
             tokenizer.number(),
@@ -512,9 +906,7 @@ mod tests {
         );
 
         let input = "-100";
-        let mut tokenizer = Tokenizer {
-            chars: input.char_indices(),
-        };
+        let mut tokenizer = Tokenizer::new(input);
 
         assert_eq!(
             tokenizer.number(),
@@ -522,9 +914,7 @@ mod tests {
         );
 
         let input = "-100.000";
-        let mut tokenizer = Tokenizer {
-            chars: input.char_indices(),
-        };
+        let mut tokenizer = Tokenizer::new(input);
 
         assert_eq!(
             tokenizer.number(),
@@ -532,9 +922,7 @@ mod tests {
         );
 
         let input = "-100.001e10";
-        let mut tokenizer = Tokenizer {
-            chars: input.char_indices(),
-        };
+        let mut tokenizer = Tokenizer::new(input);
 
         assert_eq!(
             tokenizer.number(),
@@ -545,9 +933,7 @@ mod tests {
     #[test]
     fn test_tokenizer_integer() {
         let input = "100";
-        let mut tokenizer = Tokenizer {
-            chars: input.char_indices(),
-        };
+        let mut tokenizer = Tokenizer::new(input);
 
         assert_eq!(
             tokenizer.integer(),
@@ -555,9 +941,7 @@ mod tests {
         );
 
         let input = "001";
-        let mut tokenizer = Tokenizer {
-            chars: input.char_indices(),
-        };
+        let mut tokenizer = Tokenizer::new(input);
 
         assert_eq!(
             tokenizer.integer(),
@@ -568,9 +952,7 @@ mod tests {
     #[test]
     fn test_tokenizer_fraction() {
         let input = "100";
-        let mut tokenizer = Tokenizer {
-            chars: input.char_indices(),
-        };
+        let mut tokenizer = Tokenizer::new(input);
 
         assert_eq!(
             tokenizer.fraction(),
@@ -578,13 +960,38 @@ mod tests {
         );
 
         let input = "010";
-        let mut tokenizer = Tokenizer {
-            chars: input.char_indices(),
-        };
+        let mut tokenizer = Tokenizer::new(input);
 
         assert_eq!(
             tokenizer.fraction(),
             Ok(Some(Token::Fraction(input.to_string())))
         );
     }
+
+    #[test]
+    fn test_tokenizer_skip() {
+        for input in [
+            "\"a\\n\\\"b\"",
+            "\"a\\x\"",
+            "\"a",
+            "-100.001e10",
+            "0",
+            "01",
+            "1.",
+            "1.x",
+            "1.5E+",
+            "true",
+            "nul",
+            "[",
+        ]
+        .iter()
+        {
+            let mut next = Tokenizer::new(input);
+            let mut skip = Tokenizer::new(input);
+            let token = next.next();
+
+            assert_eq!(skip.skip().is_ok(), token.is_ok(), "{}", input);
+            assert_eq!(skip.offset(), next.offset(), "{}", input);
+        }
+    }
 }
@@ -0,0 +1,393 @@
+/// Streaming JSON writer
+///
+/// writer.begin_array()?;
+/// writer.begin_object()?;
+/// writer.key("id")?;
+/// writer.number("1")?;
+/// writer.end_object()?;
+/// writer.end_array()?;
+/// writer.finish()?;
+///
+/// [{"id":1}]
+///
+/// Calls are checked as they come, so a writer never writes invalid JSON unless
+/// `non_finite_numbers` allows `NaN` and infinity. The output is formatted the same as
+/// `to_string` or `to_string_pretty` would format the equivalent `Value`, which fail at those
+/// numbers by default too.
+use std::fmt;
+use std::io;
+
+use crate::de::Value;
+use crate::number::{non_finite, Decimal};
+use crate::raw::RawValue;
+use crate::ser::{quote, NonFiniteNumbers};
+
+#[derive(Debug)]
+pub enum WriterError {
+    Io(io::Error),
+    /// A key outside of objects, or right after another key.
+    UnexpectedKey,
+    /// A value in an object without its key.
+    ExpectedKey,
+    /// An end call which does not match the innermost container, or which comes right after a key.
+    UnexpectedEnd,
+    /// A value after the root value has been completed.
+    MultipleRoots,
+    /// `finish` before the root value has been completed.
+    Incomplete,
+    /// Not a JSON number lexeme.
+    InvalidNumber(String),
+}
+
+impl fmt::Display for WriterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WriterError::Io(e) => write!(f, "{}", e),
+            WriterError::UnexpectedKey => write!(f, "key outside of an object or after a key"),
+            WriterError::ExpectedKey => write!(f, "value in an object without a key"),
+            WriterError::UnexpectedEnd => write!(f, "end of a container which is not open"),
+            WriterError::MultipleRoots => write!(f, "value after the root value"),
+            WriterError::Incomplete => write!(f, "root value is incomplete"),
+            WriterError::InvalidNumber(n) => write!(f, "invalid number {:?}", n),
+        }
+    }
+}
+
+impl std::error::Error for WriterError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WriterError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for WriterError {
+    fn from(e: io::Error) -> WriterError {
+        WriterError::Io(e)
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+enum Container {
+    Object,
+    Array,
+}
+
+struct Frame {
+    container: Container,
+    /// The number of elements or members so far.
+    len: usize,
+}
+
+/// Writes JSON to `out` call by call. Each call writes through to `out`, so wrap it with a
+/// `BufWriter` for files and sockets.
+pub struct JsonWriter<W: io::Write> {
+    out: W,
+    indent: Option<String>,
+    non_finite_numbers: NonFiniteNumbers,
+    stack: Vec<Frame>,
+    /// Whether a key has been written without its value.
+    keyed: bool,
+    done: bool,
+}
+
+impl<W: io::Write> JsonWriter<W> {
+    /// A writer without any whitespaces.
+    pub fn new(out: W) -> JsonWriter<W> {
+        JsonWriter {
+            out,
+            indent: None,
+            non_finite_numbers: NonFiniteNumbers::Error,
+            stack: vec![],
+            keyed: false,
+            done: false,
+        }
+    }
+
+    /// A writer with a line for each element or member, indented with `indent` per level.
+    pub fn pretty(out: W, indent: &str) -> JsonWriter<W> {
+        JsonWriter {
+            indent: Some(indent.to_string()),
+            ..JsonWriter::new(out)
+        }
+    }
+
+    /// How to write `NaN`, `Infinity` and `-Infinity`, which fail with
+    /// `WriterError::InvalidNumber` by default.
+    pub fn non_finite_numbers(mut self, policy: NonFiniteNumbers) -> JsonWriter<W> {
+        self.non_finite_numbers = policy;
+        self
+    }
+
+    pub fn begin_object(&mut self) -> Result<(), WriterError> {
+        self.begin(Container::Object, b'{')
+    }
+
+    pub fn end_object(&mut self) -> Result<(), WriterError> {
+        self.end(Container::Object, b'}')
+    }
+
+    pub fn begin_array(&mut self) -> Result<(), WriterError> {
+        self.begin(Container::Array, b'[')
+    }
+
+    pub fn end_array(&mut self) -> Result<(), WriterError> {
+        self.end(Container::Array, b']')
+    }
+
+    pub fn key(&mut self, key: &str) -> Result<(), WriterError> {
+        match self.stack.last() {
+            Some(frame) if frame.container == Container::Object && !self.keyed => {}
+            _ => return Err(WriterError::UnexpectedKey),
+        }
+
+        self.separate()?;
+        self.out.write_all(quote(key).as_bytes())?;
+        self.out
+            .write_all(if self.indent.is_some() { b": " } else { b":" })?;
+        self.keyed = true;
+        Ok(())
+    }
+
+    pub fn string(&mut self, s: &str) -> Result<(), WriterError> {
+        self.scalar(&quote(s))
+    }
+
+    /// Writes a number lexeme as it is, such as "1.50".
+    pub fn number(&mut self, lexeme: &str) -> Result<(), WriterError> {
+        match self.non_finite_numbers {
+            NonFiniteNumbers::Literal if non_finite(lexeme).is_some() => self.scalar(lexeme),
+            NonFiniteNumbers::Null if non_finite(lexeme).is_some() => self.null(),
+            _ if Decimal::parse(lexeme).is_none() => {
+                Err(WriterError::InvalidNumber(lexeme.to_string()))
+            }
+            _ => self.scalar(lexeme),
+        }
+    }
+
+    pub fn bool(&mut self, b: bool) -> Result<(), WriterError> {
+        self.scalar(if b { "true" } else { "false" })
+    }
+
+    pub fn null(&mut self) -> Result<(), WriterError> {
+        self.scalar("null")
+    }
+
+    /// Writes a whole value, with object members sorted by key.
+    pub fn value(&mut self, value: &Value) -> Result<(), WriterError> {
+        match value {
+            Value::Object(object) => {
+                let mut members: Vec<_> = object.iter().collect();
+                members.sort_by(|a, b| a.0.cmp(b.0));
+
+                self.begin_object()?;
+                for (key, value) in members {
+                    self.key(key)?;
+                    self.value(value)?;
+                }
+                self.end_object()
+            }
+            Value::Array(array) => {
+                self.begin_array()?;
+                for value in array.iter() {
+                    self.value(value)?;
+                }
+                self.end_array()
+            }
+            Value::String(s) => self.string(s),
+            Value::Number(n) => self.number(n),
+            Value::Bool(b) => self.bool(*b),
+            Value::Null => self.null(),
+        }
+    }
+
+    /// Writes the input text of a raw value as it is, even when pretty.
+    pub fn raw(&mut self, raw: &RawValue) -> Result<(), WriterError> {
+        self.scalar(raw.get())
+    }
+
+    /// Flushes and returns the underlying writer once the root value has been completed.
+    pub fn finish(mut self) -> Result<W, WriterError> {
+        if !self.done {
+            return Err(WriterError::Incomplete);
+        }
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    fn scalar(&mut self, s: &str) -> Result<(), WriterError> {
+        self.before_value()?;
+        self.out.write_all(s.as_bytes())?;
+        self.after_value();
+        Ok(())
+    }
+
+    fn begin(&mut self, container: Container, open: u8) -> Result<(), WriterError> {
+        self.before_value()?;
+        self.out.write_all(&[open])?;
+        self.stack.push(Frame { container, len: 0 });
+        Ok(())
+    }
+
+    fn end(&mut self, container: Container, close: u8) -> Result<(), WriterError> {
+        match self.stack.last() {
+            Some(frame) if frame.container == container && !self.keyed => {}
+            _ => return Err(WriterError::UnexpectedEnd),
+        }
+
+        let frame = self.stack.pop().unwrap();
+        if let (Some(indent), true) = (&self.indent, frame.len > 0) {
+            let line = format!("\n{}", indent.repeat(self.stack.len()));
+            self.out.write_all(line.as_bytes())?;
+        }
+        self.out.write_all(&[close])?;
+        self.after_value();
+        Ok(())
+    }
+
+    /// Checks that a value can come here, and writes what comes before it.
+    fn before_value(&mut self) -> Result<(), WriterError> {
+        match self.stack.last() {
+            None if self.done => Err(WriterError::MultipleRoots),
+            None => Ok(()),
+            Some(frame) => match frame.container {
+                Container::Object if self.keyed => {
+                    self.keyed = false;
+                    Ok(())
+                }
+                Container::Object => Err(WriterError::ExpectedKey),
+                Container::Array => self.separate(),
+            },
+        }
+    }
+
+    fn after_value(&mut self) {
+        if self.stack.is_empty() {
+            self.done = true;
+        }
+    }
+
+    /// Writes what comes before the next element or member of the innermost container.
+    fn separate(&mut self) -> Result<(), WriterError> {
+        let depth = self.stack.len();
+        let frame = self.stack.last_mut().unwrap();
+        if frame.len > 0 {
+            self.out.write_all(b",")?;
+        }
+        frame.len += 1;
+
+        if let Some(indent) = &self.indent {
+            let line = format!("\n{}", indent.repeat(depth));
+            self.out.write_all(line.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::de::parse;
+    use crate::ser::{to_string, to_string_pretty};
+
+    #[test]
+    fn test_writer() {
+        let value = parse(r#"{"b": [true, {"c": false}, 1.50], "a": {}, "d": [], "e": "x\n"}"#);
+
+        let mut writer = JsonWriter::new(vec![]);
+        writer.value(&value).unwrap();
+        let out = writer.finish().unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), to_string(&value).unwrap());
+
+        let mut writer = JsonWriter::pretty(vec![], "  ");
+        writer.value(&value).unwrap();
+        let out = writer.finish().unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            to_string_pretty(&value, "  ").unwrap()
+        );
+
+        let mut writer = JsonWriter::new(vec![]);
+        writer.begin_array().unwrap();
+        for id in 0..3 {
+            writer.begin_object().unwrap();
+            writer.key("id").unwrap();
+            writer.number(&id.to_string()).unwrap();
+            writer.key("ok").unwrap();
+            writer.bool(id != 1).unwrap();
+            writer.key("note").unwrap();
+            writer.null().unwrap();
+            writer.end_object().unwrap();
+        }
+        writer.end_array().unwrap();
+        assert_eq!(
+            String::from_utf8(writer.finish().unwrap()).unwrap(),
+            r#"[{"id":0,"ok":true,"note":null},{"id":1,"ok":false,"note":null},{"id":2,"ok":true,"note":null}]"#
+        );
+    }
+
+    #[test]
+    fn test_writer_sequence() {
+        let mut writer = JsonWriter::new(vec![]);
+        assert!(matches!(writer.key("a"), Err(WriterError::UnexpectedKey)));
+        assert!(matches!(
+            writer.end_array(),
+            Err(WriterError::UnexpectedEnd)
+        ));
+        writer.begin_object().unwrap();
+        assert!(matches!(writer.null(), Err(WriterError::ExpectedKey)));
+        assert!(matches!(
+            writer.end_array(),
+            Err(WriterError::UnexpectedEnd)
+        ));
+        writer.key("a").unwrap();
+        assert!(matches!(writer.key("b"), Err(WriterError::UnexpectedKey)));
+        assert!(matches!(
+            writer.end_object(),
+            Err(WriterError::UnexpectedEnd)
+        ));
+        assert!(matches!(
+            writer.number("01"),
+            Err(WriterError::InvalidNumber(_))
+        ));
+        writer.begin_array().unwrap();
+        assert!(matches!(writer.key("c"), Err(WriterError::UnexpectedKey)));
+        writer.end_array().unwrap();
+        writer.end_object().unwrap();
+        assert!(matches!(writer.null(), Err(WriterError::MultipleRoots)));
+        assert_eq!(writer.finish().unwrap(), br#"{"a":[]}"#.to_vec());
+
+        let mut writer = JsonWriter::new(vec![]);
+        writer.begin_array().unwrap();
+        assert!(matches!(writer.finish(), Err(WriterError::Incomplete)));
+
+        for (policy, expected) in [
+            (NonFiniteNumbers::Literal, Some("[NaN,-Infinity]")),
+            (NonFiniteNumbers::Null, Some("[null,null]")),
+            (NonFiniteNumbers::Error, None),
+        ]
+        .iter()
+        {
+            let mut writer = JsonWriter::new(vec![]).non_finite_numbers(*policy);
+            writer.begin_array().unwrap();
+            let result = writer
+                .number("NaN")
+                .and_then(|_| writer.number("-Infinity"));
+            assert_eq!(result.is_ok(), expected.is_some());
+            if let Some(expected) = expected {
+                writer.end_array().unwrap();
+                assert_eq!(writer.finish().unwrap(), expected.as_bytes());
+            }
+        }
+
+        // Both fail at non-finite numbers by default.
+        let value = Value::Array(vec![Value::Number("NaN".to_string())]);
+        assert!(to_string(&value).is_err());
+        let mut writer = JsonWriter::new(vec![]);
+        assert!(matches!(
+            writer.value(&value),
+            Err(WriterError::InvalidNumber(_))
+        ));
+    }
+}
@@ -0,0 +1,539 @@
+// Derive macros for the `FromJson` and `ToJson` traits of ym
+//
+// #[derive(FromJson, ToJson)]
+// struct Config {
+//     #[json(rename = "hostname")]
+//     host: String,
+//     #[json(default)]
+//     port: u16,
+//     #[json(skip)]
+//     cache: Vec<u8>,
+//     #[json(flatten)]
+//     extra: Extra,
+// }
+//
+// - Structs with named fields are objects, newtype structs are their field, tuple structs are
+//   arrays and unit structs are null.
+// - Unit variants are strings, and other variants are objects with a single member whose key is
+//   the variant name, e.g. {"Move": {"x": 1}}.
+// - Unknown object members are ignored.
+//
+// The input is parsed by hand, and generics are not supported.
+
+use proc_macro::{Delimiter, Spacing, TokenStream, TokenTree};
+
+#[proc_macro_derive(FromJson, attributes(json))]
+pub fn derive_from_json(input: TokenStream) -> TokenStream {
+    expand(input, from_json)
+}
+
+#[proc_macro_derive(ToJson, attributes(json))]
+pub fn derive_to_json(input: TokenStream) -> TokenStream {
+    expand(input, to_json)
+}
+
+fn expand(input: TokenStream, generate: fn(&Item) -> String) -> TokenStream {
+    let code = match parse_item(input) {
+        Ok(item) => generate(&item),
+        Err(message) => format!("compile_error!({:?});", message),
+    };
+
+    code.parse().expect("generated code should be valid tokens")
+}
+
+struct Item {
+    name: String,
+    data: Data,
+}
+
+enum Data {
+    Struct(Fields),
+    Enum(Vec<Variant>),
+}
+
+struct Variant {
+    name: String,
+    /// String literal of the JSON name.
+    key: String,
+    fields: Fields,
+}
+
+enum Fields {
+    Named(Vec<Field>),
+    /// The number of fields.
+    Unnamed(usize),
+    Unit,
+}
+
+struct Field {
+    name: String,
+    /// String literal of the JSON name.
+    key: String,
+    attrs: Attrs,
+}
+
+#[derive(Default)]
+struct Attrs {
+    rename: Option<String>,
+    default: bool,
+    skip: bool,
+    flatten: bool,
+}
+
+/// Tokens and the current position in them.
+struct Cursor {
+    tokens: Vec<TokenTree>,
+    pos: usize,
+}
+
+impl Cursor {
+    fn new(stream: TokenStream) -> Cursor {
+        Cursor {
+            tokens: stream.into_iter().collect(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<&TokenTree> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<TokenTree> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn is_end(&self) -> bool {
+        self.pos >= self.tokens.len()
+    }
+
+    fn peek_punct(&self, c: char) -> bool {
+        matches!(self.peek(), Some(TokenTree::Punct(p)) if p.as_char() == c)
+    }
+
+    fn peek_ident(&self, s: &str) -> bool {
+        matches!(self.peek(), Some(TokenTree::Ident(i)) if i.to_string() == s)
+    }
+
+    fn ident(&mut self) -> Result<String, String> {
+        match self.next() {
+            Some(TokenTree::Ident(i)) => Ok(i.to_string()),
+            token => Err(format!("expected an identifier, found {}", describe(token))),
+        }
+    }
+
+    /// Outer attributes, of which only `#[json(...)]` are interpreted.
+    fn attrs(&mut self) -> Result<Attrs, String> {
+        let mut attrs = Attrs::default();
+
+        while self.peek_punct('#') {
+            self.next();
+            let group = match self.next() {
+                Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Bracket => g,
+                token => return Err(format!("expected an attribute, found {}", describe(token))),
+            };
+
+            let mut inner = Cursor::new(group.stream());
+            if !inner.peek_ident("json") {
+                continue;
+            }
+            inner.next();
+
+            match inner.next() {
+                Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis => {
+                    parse_json_attr(g.stream(), &mut attrs)?
+                }
+                _ => return Err("expected #[json(...)]".to_string()),
+            }
+        }
+
+        Ok(attrs)
+    }
+
+    /// `pub`, `pub(crate)` and so on.
+    fn skip_visibility(&mut self) {
+        if self.peek_ident("pub") {
+            self.next();
+            if let Some(TokenTree::Group(g)) = self.peek() {
+                if g.delimiter() == Delimiter::Parenthesis {
+                    self.next();
+                }
+            }
+        }
+    }
+}
+
+fn describe(token: Option<TokenTree>) -> String {
+    match token {
+        Some(token) => format!("`{}`", token),
+        None => "the end".to_string(),
+    }
+}
+
+fn parse_json_attr(stream: TokenStream, attrs: &mut Attrs) -> Result<(), String> {
+    for segment in split_commas(stream) {
+        let mut cursor = Cursor {
+            tokens: segment,
+            pos: 0,
+        };
+
+        match cursor.ident()?.as_str() {
+            "rename" => {
+                if !cursor.peek_punct('=') {
+                    return Err("expected `rename = \"...\"`".to_string());
+                }
+                cursor.next();
+                match cursor.next() {
+                    Some(TokenTree::Literal(l)) if l.to_string().starts_with('"') => {
+                        attrs.rename = Some(l.to_string())
+                    }
+                    _ => return Err("expected `rename = \"...\"`".to_string()),
+                }
+            }
+            "default" => attrs.default = true,
+            "skip" => attrs.skip = true,
+            "flatten" => attrs.flatten = true,
+            name => return Err(format!("unknown json attribute `{}`", name)),
+        }
+
+        if !cursor.is_end() {
+            return Err(format!("unexpected {}", describe(cursor.next())));
+        }
+    }
+
+    Ok(())
+}
+
+/// Splits tokens by top-level commas, skipping empty segments. Commas in generic arguments such
+/// as `HashMap<K, V>` are not top-level.
+fn split_commas(stream: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut segments = vec![vec![]];
+    let mut depth = 0;
+    let mut arrow = false;
+
+    for token in stream {
+        if let TokenTree::Punct(p) = &token {
+            match p.as_char() {
+                ',' if depth == 0 => {
+                    segments.push(vec![]);
+                    continue;
+                }
+                '<' => depth += 1,
+                // `->` of function types is not a closing bracket.
+                '>' if !arrow => depth -= 1,
+                _ => {}
+            }
+            arrow = p.as_char() == '-' && p.spacing() == Spacing::Joint;
+        } else {
+            arrow = false;
+        }
+
+        segments.last_mut().unwrap().push(token);
+    }
+
+    segments.retain(|segment| !segment.is_empty());
+    segments
+}
+
+/// A JSON name from a Rust identifier, which may be raw such as `r#type`.
+fn key(name: &str, attrs: &Attrs) -> String {
+    match &attrs.rename {
+        Some(rename) => rename.clone(),
+        None => format!("{:?}", name.trim_start_matches("r#")),
+    }
+}
+
+fn parse_item(input: TokenStream) -> Result<Item, String> {
+    let mut cursor = Cursor::new(input);
+    cursor.attrs()?;
+    cursor.skip_visibility();
+
+    let kind = cursor.ident()?;
+    let name = cursor.ident()?;
+    if cursor.peek_punct('<') {
+        return Err(format!("generics of `{}` are not supported", name));
+    }
+    if cursor.peek_ident("where") {
+        return Err(format!("where clauses of `{}` are not supported", name));
+    }
+
+    let data = match kind.as_str() {
+        "struct" => Data::Struct(parse_fields(&mut cursor)?),
+        "enum" => match cursor.next() {
+            Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Brace => {
+                Data::Enum(parse_variants(g.stream())?)
+            }
+            token => return Err(format!("expected variants, found {}", describe(token))),
+        },
+        _ => return Err(format!("`{}` is not supported", kind)),
+    };
+
+    Ok(Item { name, data })
+}
+
+/// Fields after a struct or variant name, if any.
+fn parse_fields(cursor: &mut Cursor) -> Result<Fields, String> {
+    let group = match cursor.peek() {
+        Some(TokenTree::Group(g)) if g.delimiter() != Delimiter::Bracket => g.clone(),
+        _ => return Ok(Fields::Unit),
+    };
+    cursor.next();
+
+    if group.delimiter() == Delimiter::Parenthesis {
+        return Ok(Fields::Unnamed(split_commas(group.stream()).len()));
+    }
+
+    let mut fields = vec![];
+    for segment in split_commas(group.stream()) {
+        let mut cursor = Cursor {
+            tokens: segment,
+            pos: 0,
+        };
+        let attrs = cursor.attrs()?;
+        cursor.skip_visibility();
+        let name = cursor.ident()?;
+        if !cursor.peek_punct(':') {
+            return Err(format!("expected a type of `{}`", name));
+        }
+
+        fields.push(Field {
+            key: key(&name, &attrs),
+            name,
+            attrs,
+        });
+    }
+
+    Ok(Fields::Named(fields))
+}
+
+fn parse_variants(stream: TokenStream) -> Result<Vec<Variant>, String> {
+    let mut variants = vec![];
+
+    for segment in split_commas(stream) {
+        let mut cursor = Cursor {
+            tokens: segment,
+            pos: 0,
+        };
+        let attrs = cursor.attrs()?;
+        let name = cursor.ident()?;
+        let fields = parse_fields(&mut cursor)?;
+
+        variants.push(Variant {
+            key: key(&name, &attrs),
+            name,
+            fields,
+        });
+    }
+
+    Ok(variants)
+}
+
+const VALUE: &str = "::ym::Value";
+const ERROR: &str = "::ym::FromJsonError";
+const MAP: &str = "::std::collections::HashMap";
+
+fn to_json(item: &Item) -> String {
+    let body = match &item.data {
+        Data::Struct(Fields::Named(fields)) => {
+            named_to_json(fields, |name| format!("&self.{}", name))
+        }
+        Data::Struct(Fields::Unnamed(1)) => "::ym::ToJson::to_json(&self.0)".to_string(),
+        Data::Struct(Fields::Unnamed(n)) => {
+            let values: Vec<String> = (0..*n)
+                .map(|i| format!("::ym::ToJson::to_json(&self.{})", i))
+                .collect();
+            format!("{}::Array(vec![{}])", VALUE, values.join(", "))
+        }
+        Data::Struct(Fields::Unit) => format!("{}::Null", VALUE),
+        Data::Enum(variants) => {
+            let arms: Vec<String> = variants.iter().map(variant_to_json).collect();
+            format!("match *self {{ {} }}", arms.join(" "))
+        }
+    };
+
+    format!(
+        "impl ::ym::ToJson for {} {{ fn to_json(&self) -> {} {{ {} }} }}",
+        item.name, VALUE, body
+    )
+}
+
+/// An object of the fields, where `access` is a reference expression of a field.
+fn named_to_json(fields: &[Field], access: impl Fn(&str) -> String) -> String {
+    let mut code = format!("let mut object = {}::new();", MAP);
+
+    for field in fields.iter().filter(|f| !f.attrs.skip) {
+        let value = format!("::ym::ToJson::to_json({})", access(&field.name));
+        if field.attrs.flatten {
+            code += &format!(
+                "if let {}::Object(inner) = {} {{ object.extend(inner); }}",
+                VALUE, value
+            );
+        } else {
+            code += &format!("object.insert({}.to_string(), {});", field.key, value);
+        }
+    }
+
+    format!("{} {}::Object(object)", code, VALUE)
+}
+
+fn variant_to_json(variant: &Variant) -> String {
+    let (pattern, value) = match &variant.fields {
+        Fields::Unit => {
+            return format!(
+                "Self::{} => {}::String({}.to_string()),",
+                variant.name, VALUE, variant.key
+            )
+        }
+        Fields::Unnamed(n) => {
+            let names: Vec<String> = (0..*n).map(|i| format!("ref f{}", i)).collect();
+            let values: Vec<String> = (0..*n)
+                .map(|i| format!("::ym::ToJson::to_json(f{})", i))
+                .collect();
+            let value = match values.len() {
+                1 => values[0].clone(),
+                _ => format!("{}::Array(vec![{}])", VALUE, values.join(", ")),
+            };
+            (format!("({})", names.join(", ")), value)
+        }
+        Fields::Named(fields) => {
+            let mut names: Vec<String> = fields
+                .iter()
+                .filter(|f| !f.attrs.skip)
+                .map(|f| format!("ref {}", f.name))
+                .collect();
+            names.push("..".to_string());
+            let value = format!("{{ {} }}", named_to_json(fields, |name| name.to_string()));
+            (format!("{{ {} }}", names.join(", ")), value)
+        }
+    };
+
+    format!(
+        "Self::{}{} => {{ let mut tagged = {}::new(); tagged.insert({}.to_string(), {}); {}::Object(tagged) }}",
+        variant.name, pattern, MAP, variant.key, value, VALUE
+    )
+}
+
+fn from_json(item: &Item) -> String {
+    let body = match &item.data {
+        Data::Struct(fields) => fields_from_json(&item.name, &item.name, fields),
+        Data::Enum(variants) => enum_from_json(&item.name, variants),
+    };
+
+    format!(
+        "impl ::ym::FromJson for {} {{ fn from_json(value: &{}) -> ::std::result::Result<Self, {}> {{ {} }} }}",
+        item.name, VALUE, ERROR, body
+    )
+}
+
+/// An expression of `Result<Self, FromJsonError>` which constructs `path` from `value`.
+fn fields_from_json(name: &str, path: &str, fields: &Fields) -> String {
+    let expected = format!(
+        "::std::result::Result::Err({}::expected({:?}, value))",
+        ERROR, name
+    );
+
+    match fields {
+        Fields::Named(fields) => {
+            let members: Vec<String> = fields
+                .iter()
+                .map(|field| {
+                    let value = if field.attrs.skip {
+                        "::std::default::Default::default()".to_string()
+                    } else if field.attrs.flatten {
+                        "::ym::FromJson::from_json(value)?".to_string()
+                    } else {
+                        let missing = if field.attrs.default {
+                            "::std::default::Default::default()".to_string()
+                        } else {
+                            format!("::ym::FromJson::from_missing({})?", field.key)
+                        };
+                        format!(
+                            "match object.get({0}) {{ ::std::option::Option::Some(v) => ::ym::FromJson::from_json(v).map_err(|e: {1}| e.at_key({0}))?, ::std::option::Option::None => {2} }}",
+                            field.key, ERROR, missing
+                        )
+                    };
+                    format!("{}: {},", field.name, value)
+                })
+                .collect();
+
+            format!(
+                "#[allow(unused_variables)] let object = match value {{ {}::Object(object) => object, _ => return {} }}; ::std::result::Result::Ok({} {{ {} }})",
+                VALUE, expected, path, members.join(" ")
+            )
+        }
+        Fields::Unnamed(1) => format!(
+            "::std::result::Result::Ok({}(::ym::FromJson::from_json(value)?))",
+            path
+        ),
+        Fields::Unnamed(n) => {
+            let values: Vec<String> = (0..*n)
+                .map(|i| {
+                    format!(
+                        "::ym::FromJson::from_json(&array[{0}]).map_err(|e: {1}| e.at_index({0}))?",
+                        i, ERROR
+                    )
+                })
+                .collect();
+            format!(
+                "match value {{ {}::Array(array) if array.len() == {} => ::std::result::Result::Ok({}({})), _ => {} }}",
+                VALUE, n, path, values.join(", "), expected
+            )
+        }
+        Fields::Unit => format!(
+            "match value {{ {}::Null => ::std::result::Result::Ok({}), _ => {} }}",
+            VALUE, path, expected
+        ),
+    }
+}
+
+fn enum_from_json(name: &str, variants: &[Variant]) -> String {
+    let unknown = format!(
+        "_ => ::std::result::Result::Err({}::UnknownVariant(name.clone())),",
+        ERROR
+    );
+    let mut arms = vec![];
+
+    let units: Vec<String> = variants
+        .iter()
+        .filter(|v| matches!(v.fields, Fields::Unit))
+        .map(|v| format!("{} => ::std::result::Result::Ok(Self::{}),", v.key, v.name))
+        .collect();
+    if !units.is_empty() {
+        arms.push(format!(
+            "{}::String(name) => match name.as_str() {{ {} {} }},",
+            VALUE,
+            units.join(" "),
+            unknown
+        ));
+    }
+
+    let tagged: Vec<String> = variants
+        .iter()
+        .filter(|v| !matches!(v.fields, Fields::Unit))
+        .map(|v| {
+            let path = format!("Self::{}", v.name);
+            format!(
+                "{0} => (|| -> ::std::result::Result<Self, {1}> {{ {2} }})().map_err(|e| e.at_key({0})),",
+                v.key,
+                ERROR,
+                fields_from_json(name, &path, &v.fields)
+            )
+        })
+        .collect();
+    if !tagged.is_empty() {
+        arms.push(format!(
+            "{}::Object(tagged) if tagged.len() == 1 => {{ let (name, value) = tagged.iter().next().unwrap(); match name.as_str() {{ {} {} }} }}",
+            VALUE,
+            tagged.join(" "),
+            unknown
+        ));
+    }
+
+    arms.push(format!(
+        "_ => ::std::result::Result::Err({}::expected({:?}, value)),",
+        ERROR, name
+    ));
+
+    format!("match value {{ {} }}", arms.join(" "))
+}
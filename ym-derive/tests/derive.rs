@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use ym::{json, FromJson, FromJsonError, ToJson, Value};
+
+#[derive(FromJson, ToJson, Default, PartialEq, Debug)]
+struct Extra {
+    verbose: bool,
+    tags: Option<Vec<String>>,
+}
+
+#[derive(FromJson, ToJson, PartialEq, Debug)]
+pub struct Config {
+    #[json(rename = "hostname")]
+    pub host: String,
+    #[json(default)]
+    port: u16,
+    #[json(skip)]
+    cache: Vec<u8>,
+    #[json(flatten)]
+    extra: Extra,
+    r#type: Option<Kind>,
+    limits: HashMap<String, u64>,
+}
+
+#[derive(FromJson, ToJson, Clone, PartialEq, Debug)]
+enum Kind {
+    Primary,
+    #[json(rename = "secondary")]
+    Secondary,
+    Weighted(u8),
+    Range(i32, i32),
+    Custom {
+        name: String,
+        #[json(default)]
+        weight: Option<f64>,
+        #[json(skip)]
+        cached: bool,
+    },
+}
+
+#[derive(FromJson, ToJson, PartialEq, Debug)]
+struct Meters(f64);
+
+#[derive(FromJson, ToJson, PartialEq, Debug)]
+struct Point(i64, i64);
+
+#[derive(FromJson, ToJson, PartialEq, Debug)]
+struct Marker;
+
+fn round_trip<T: FromJson + ToJson + PartialEq + std::fmt::Debug>(value: T, json: Value) {
+    assert_eq!(value.to_json(), json);
+    assert_eq!(T::from_json(&json), Ok(value));
+}
+
+#[test]
+fn test_derive_struct() {
+    let mut limits = HashMap::new();
+    limits.insert("cpu".to_string(), 2);
+
+    round_trip(
+        Config {
+            host: "example.com".to_string(),
+            port: 8080,
+            cache: vec![],
+            extra: Extra {
+                verbose: true,
+                tags: Some(vec!["a".to_string()]),
+            },
+            r#type: Some(Kind::Weighted(3)),
+            limits,
+        },
+        json!({
+            "hostname": "example.com",
+            "port": 8080,
+            "verbose": true,
+            "tags": ["a"],
+            "type": {"Weighted": 3},
+            "limits": {"cpu": 2}
+        }),
+    );
+
+    let config = Config::from_json(&json!({
+        "hostname": "localhost",
+        "verbose": false,
+        "limits": {},
+        "cache": [1],
+        "unknown": null
+    }))
+    .unwrap();
+    assert_eq!(config.port, 0);
+    assert_eq!(config.cache, Vec::<u8>::new());
+    assert_eq!(config.extra, Extra::default());
+    assert_eq!(config.r#type, None);
+
+    round_trip(Meters(1.5), json!(1.5));
+    round_trip(Point(1, -2), json!([1, -2]));
+    round_trip(Marker, json!(null));
+}
+
+#[test]
+fn test_derive_enum() {
+    round_trip(Kind::Primary, json!("Primary"));
+    round_trip(Kind::Secondary, json!("secondary"));
+    round_trip(Kind::Range(-1, 1), json!({"Range": [-1, 1]}));
+    round_trip(
+        Kind::Custom {
+            name: "x".to_string(),
+            weight: Some(0.5),
+            cached: false,
+        },
+        json!({"Custom": {"name": "x", "weight": 0.5}}),
+    );
+
+    assert_eq!(
+        Kind::from_json(&json!({"Custom": {"name": "x"}})),
+        Ok(Kind::Custom {
+            name: "x".to_string(),
+            weight: None,
+            cached: false
+        })
+    );
+}
+
+#[test]
+fn test_derive_errors() {
+    assert_eq!(
+        Config::from_json(&json!({"verbose": true, "limits": {}})),
+        Err(FromJsonError::MissingField("hostname"))
+    );
+    assert_eq!(
+        Config::from_json(&json!({"hostname": "a", "limits": {}})),
+        Err(FromJsonError::MissingField("verbose"))
+    );
+    assert_eq!(
+        Kind::from_json(&json!("Tertiary")),
+        Err(FromJsonError::UnknownVariant("Tertiary".to_string()))
+    );
+    assert_eq!(
+        Kind::from_json(&json!({"Primary": null})),
+        Err(FromJsonError::UnknownVariant("Primary".to_string()))
+    );
+
+    let messages = [
+        (
+            Point::from_json(&json!([1])).unwrap_err(),
+            "expected Point, found array",
+        ),
+        (
+            Kind::from_json(&json!(1)).unwrap_err(),
+            "expected Kind, found number 1",
+        ),
+        (
+            Config::from_json(&json!({"hostname": 1})).unwrap_err(),
+            "invalid type at `$.hostname`: expected String, found number 1",
+        ),
+        (
+            Config::from_json(&json!({
+                "hostname": "a",
+                "verbose": true,
+                "type": {"Range": [0, "1"]},
+                "limits": {}
+            }))
+            .unwrap_err(),
+            "invalid type at `$.type.Range[1]`: expected i32, found string",
+        ),
+        (
+            Config::from_json(&json!({
+                "hostname": "a",
+                "verbose": true,
+                "limits": {"cpu": -1}
+            }))
+            .unwrap_err(),
+            "invalid type at `$.limits.cpu`: expected u64, found number -1",
+        ),
+        (
+            Kind::from_json(&json!({"Custom": {"weight": 1}})).unwrap_err(),
+            "missing field \"name\" at `$.Custom`",
+        ),
+        (
+            Marker::from_json(&json!(false)).unwrap_err(),
+            "expected Marker, found boolean false",
+        ),
+    ];
+    for (error, message) in messages.iter() {
+        assert_eq!(error.to_string(), *message);
+    }
+}